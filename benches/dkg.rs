@@ -4,22 +4,21 @@ use aggregatable_dkg::{
         config::Config,
         dealer::Dealer,
         node::Node,
-        participant::{Participant, ParticipantState},
-        share::DKGTranscript,
+        participant::{committee_digest, Participant, ParticipantState},
+        prepared::PreparedG2,
         srs::SRS,
     },
     signature::{
         bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1, BLSSignatureG2},
-        scheme::BatchVerifiableSignatureScheme,
+        scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
         schnorr::{srs::SRS as SchnorrSRS, SchnorrSignature},
     },
 };
 use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine, G2Projective};
-use ark_ec::ProjectiveCurve;
+use ark_ec::{PairingEngine, ProjectiveCurve};
 use ark_ff::{UniformRand, Zero};
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::thread_rng;
-use std::marker::PhantomData;
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let rng = &mut thread_rng();
@@ -59,7 +58,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 fn benchmark_with_signature_scheme<
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = G2Affine, Secret = Fr>,
 >(
     c: &mut Criterion,
@@ -75,20 +74,13 @@ fn benchmark_with_signature_scheme<
             let rng = &mut thread_rng();
 
             let u_1 = G2Projective::rand(rng).into_affine();
-            let dkg_config = Config {
-                srs: srs.clone(),
-                u_1,
-                degree,
-            };
 
             let mut dealers = vec![];
             for i in 0..num_nodes {
                 let dealer_keypair_sig = ssig.generate_keypair(rng).unwrap();
                 let participant = Participant {
-                    pairing_type: PhantomData,
-                    id: i,
-                    public_key_sig: dealer_keypair_sig.1,
                     state: ParticipantState::Dealer,
+                    ..Participant::register(rng, &ssig, i, &dealer_keypair_sig.0).unwrap()
                 };
                 let dealer = Dealer {
                     private_key_sig: dealer_keypair_sig.0,
@@ -103,27 +95,29 @@ fn benchmark_with_signature_scheme<
                 .iter()
                 .map(|d| d.participant.clone())
                 .collect::<Vec<_>>();
-            let num_participants = participants.len();
 
-            let mut aggregator = DKGAggregator {
-                config: dkg_config.clone(),
-                scheme_pok: spok.clone(),
-                scheme_sig: ssig.clone(),
-                participants: participants.clone().into_iter().enumerate().collect(),
-                transcript: DKGTranscript::empty(dkg_config.degree, num_participants),
+            let dkg_config = Config {
+                srs: srs.clone(),
+                u_1,
+                degree,
+                session_id: b"session".to_vec(),
+                committee_digest: committee_digest(&participants).unwrap(),
             };
 
+            let empty_aggregator = DKGAggregator::new(
+                rng,
+                dkg_config.clone(),
+                spok.clone(),
+                ssig.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap();
+            let mut aggregator = empty_aggregator.clone();
+
             let mut nodes = vec![];
             for i in 0..num_nodes {
-                let degree = dkg_config.degree;
                 let node = Node {
-                    aggregator: DKGAggregator {
-                        config: dkg_config.clone(),
-                        scheme_pok: spok.clone(),
-                        scheme_sig: ssig.clone(),
-                        participants: participants.clone().into_iter().enumerate().collect(),
-                        transcript: DKGTranscript::empty(degree, num_participants),
-                    },
+                    aggregator: empty_aggregator.clone(),
                     dealer: dealers[i].clone(),
                 };
                 nodes.push(node);
@@ -151,25 +145,66 @@ fn benchmark_with_signature_scheme<
                         ),
                         |b| {
                             b.iter(|| {
-                                let mut aggregator = DKGAggregator {
-                                    config: dkg_config.clone(),
-                                    scheme_pok: spok.clone(),
-                                    scheme_sig: ssig.clone(),
-                                    participants: participants
-                                        .clone()
-                                        .into_iter()
-                                        .enumerate()
-                                        .collect(),
-                                    transcript: DKGTranscript::empty(
-                                        dkg_config.degree,
-                                        num_participants,
-                                    ),
-                                };
+                                let mut aggregator = empty_aggregator.clone();
 
                                 aggregator.receive_share(rng, &share.clone()).unwrap();
                             })
                         },
                     );
+                    c.bench_function(
+                        &format!(
+                            "dkg(sig_scheme={}, nodes={}, degree={}) aggregator pvss share verify",
+                            tag, num_nodes, degree
+                        ),
+                        |b| {
+                            b.iter(|| {
+                                empty_aggregator
+                                    .pvss_share_verify(rng, share.c_i, &share.pvss_share)
+                                    .unwrap()
+                            })
+                        },
+                    );
+                    // The pairings of the encrypted evaluations with the participants' keys, with
+                    // the keys prepared once as the aggregator does, and prepared on every call.
+                    let prepared = PreparedG2::new(&dkg_config, &empty_aggregator.participants);
+                    c.bench_function(
+                        &format!(
+                            "dkg(sig_scheme={}, nodes={}, degree={}) pairings with prepared G2 points",
+                            tag, num_nodes, degree
+                        ),
+                        |b| {
+                            b.iter(|| {
+                                let pairs = share
+                                    .pvss_share
+                                    .a_i
+                                    .iter()
+                                    .zip(prepared.public_keys_sig.iter())
+                                    .map(|(a, public_key_sig)| ((*a).into(), public_key_sig.clone()))
+                                    .collect::<Vec<_>>();
+                                Bls12_381::product_of_pairings(&pairs)
+                            })
+                        },
+                    );
+                    c.bench_function(
+                        &format!(
+                            "dkg(sig_scheme={}, nodes={}, degree={}) pairings with unprepared G2 points",
+                            tag, num_nodes, degree
+                        ),
+                        |b| {
+                            b.iter(|| {
+                                let pairs = share
+                                    .pvss_share
+                                    .a_i
+                                    .iter()
+                                    .zip(empty_aggregator.participants.values())
+                                    .map(|(a, participant)| {
+                                        ((*a).into(), participant.public_key_sig.into())
+                                    })
+                                    .collect::<Vec<_>>();
+                                Bls12_381::product_of_pairings(&pairs)
+                            })
+                        },
+                    );
                 }
             }
 
@@ -177,13 +212,7 @@ fn benchmark_with_signature_scheme<
             for i in 0..num_nodes {
                 let degree = dkg_config.degree;
                 let mut node = Node {
-                    aggregator: DKGAggregator {
-                        config: dkg_config.clone(),
-                        scheme_pok: spok.clone(),
-                        scheme_sig: ssig.clone(),
-                        participants: participants.clone().into_iter().enumerate().collect(),
-                        transcript: DKGTranscript::empty(degree, num_participants),
-                    },
+                    aggregator: empty_aggregator.clone(),
                     dealer: dealers[i].clone(),
                 };
                 node.receive_transcript_and_decrypt(rng, transcript.clone())
@@ -201,17 +230,7 @@ fn benchmark_with_signature_scheme<
                         |b| {
                             b.iter(|| {
                                 let mut node = Node {
-                                    aggregator: DKGAggregator {
-                                        config: dkg_config.clone(),
-                                        scheme_pok: spok.clone(),
-                                        scheme_sig: ssig.clone(),
-                                        participants: participants
-                                            .clone()
-                                            .into_iter()
-                                            .enumerate()
-                                            .collect(),
-                                        transcript: DKGTranscript::empty(degree, num_participants),
-                                    },
+                                    aggregator: empty_aggregator.clone(),
                                     dealer: dealers[i].clone(),
                                 };
                                 node.receive_transcript_and_decrypt(rng, transcript.clone())
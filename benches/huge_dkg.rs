@@ -3,26 +3,26 @@ use aggregatable_dkg::{
         aggregator::DKGAggregator,
         config::Config,
         dealer::Dealer,
+        domain::ParticipantDomain,
         errors::DKGError,
         node::Node,
-        participant::{Participant, ParticipantState},
+        participant::{committee_digest, Participant, ParticipantState},
         pvss::PVSSShare,
-        share::{message_from_c_i, DKGTranscript, DKGTranscriptParticipant},
+        share::{message_from_c_i, ContributionPok, DKGTranscript, DKGTranscriptParticipant},
         srs::SRS,
     },
     signature::{
         bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1, BLSSignatureG2},
-        scheme::BatchVerifiableSignatureScheme,
+        scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
         schnorr::{srs::SRS as SchnorrSRS, SchnorrSignature},
+        utils::batch::BatchScalars,
     },
 };
 use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine, G2Projective};
 use ark_ec::{AffineCurve, ProjectiveCurve};
 use ark_ff::{PrimeField, UniformRand, Zero};
-use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use criterion::{criterion_group, criterion_main, Criterion};
 use rand::thread_rng;
-use std::marker::PhantomData;
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let rng = &mut thread_rng();
@@ -62,7 +62,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 fn benchmark_with_signature_scheme<
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = G2Affine, Secret = Fr>,
 >(
     c: &mut Criterion,
@@ -77,20 +77,13 @@ fn benchmark_with_signature_scheme<
         let rng = &mut thread_rng();
 
         let u_1 = G2Projective::rand(rng).into_affine();
-        let dkg_config = Config {
-            srs: srs.clone(),
-            u_1,
-            degree,
-        };
 
         let mut dealers = vec![];
         for i in 0..num_nodes {
             let dealer_keypair_sig = ssig.generate_keypair(rng).unwrap();
             let participant = Participant {
-                pairing_type: PhantomData,
-                id: i,
-                public_key_sig: dealer_keypair_sig.1,
                 state: ParticipantState::Dealer,
+                ..Participant::register(rng, &ssig, i, &dealer_keypair_sig.0).unwrap()
             };
             let dealer = Dealer {
                 private_key_sig: dealer_keypair_sig.0,
@@ -105,8 +98,24 @@ fn benchmark_with_signature_scheme<
             .iter()
             .map(|d| d.participant.clone())
             .collect::<Vec<_>>();
+
+        let dkg_config = Config {
+            srs: srs.clone(),
+            u_1,
+            degree,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(&participants).unwrap(),
+        };
         let num_participants = participants.len();
 
+        let empty_aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            spok.clone(),
+            ssig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
         c.bench_function(
             &format!(
                 "huge-dkg(j={}, sig_scheme={}, nodes={}, degree={}) share",
@@ -115,13 +124,7 @@ fn benchmark_with_signature_scheme<
             |b| {
                 b.iter(|| {
                     let mut node = Node {
-                        aggregator: DKGAggregator {
-                            config: dkg_config.clone(),
-                            scheme_pok: spok.clone(),
-                            scheme_sig: ssig.clone(),
-                            participants: participants.clone().into_iter().enumerate().collect(),
-                            transcript: DKGTranscript::empty(degree, num_participants),
-                        },
+                        aggregator: empty_aggregator.clone(),
                         dealer: dealers[0].clone(),
                     };
                     node.share(rng).unwrap()
@@ -137,23 +140,11 @@ fn benchmark_with_signature_scheme<
             |b| {
                 b.iter(|| {
                     let mut node = Node {
-                        aggregator: DKGAggregator {
-                            config: dkg_config.clone(),
-                            scheme_pok: spok.clone(),
-                            scheme_sig: ssig.clone(),
-                            participants: participants.clone().into_iter().enumerate().collect(),
-                            transcript: DKGTranscript::empty(degree, num_participants),
-                        },
+                        aggregator: empty_aggregator.clone(),
                         dealer: dealers[0].clone(),
                     };
                     let share = node.share(rng).unwrap();
-                    let mut aggregator = DKGAggregator {
-                        config: dkg_config.clone(),
-                        scheme_pok: spok.clone(),
-                        scheme_sig: ssig.clone(),
-                        participants: participants.clone().into_iter().enumerate().collect(),
-                        transcript: DKGTranscript::empty(dkg_config.degree, num_participants),
-                    };
+                    let mut aggregator = empty_aggregator.clone();
 
                     aggregator.receive_share(rng, &share.clone()).unwrap();
                 })
@@ -172,7 +163,7 @@ fn benchmark_with_signature_scheme<
                 .sign(
                     rng,
                     &pok_keypair.0,
-                    &message_from_c_i::<Bls12_381>(c.clone()).unwrap(),
+                    &message_from_c_i(&dkg_config, c).unwrap(),
                 )
                 .unwrap();
 
@@ -181,14 +172,14 @@ fn benchmark_with_signature_scheme<
                 .sign(
                     rng,
                     &signature_keypair.0,
-                    &message_from_c_i::<Bls12_381>(c.clone()).unwrap(),
+                    &message_from_c_i(&dkg_config, c).unwrap(),
                 )
                 .unwrap();
 
             let transcript_participant = DKGTranscriptParticipant::<Bls12_381, SPOK, SSIG> {
                 c_i: c.clone(),
                 weight: 1,
-                c_i_pok: pok,
+                c_i_pok: ContributionPok::Signature(pok),
                 signature_on_c_i: signature,
             };
             accumulated_r += &r;
@@ -200,10 +191,10 @@ fn benchmark_with_signature_scheme<
             .map(|_| Fr::rand(rng))
             .collect::<Vec<_>>();
         f[0] = accumulated_r;
-        let domain = Radix2EvaluationDomain::<Fr>::new(participants.len())
+        let domain = ParticipantDomain::<Fr>::new(participants.len())
             .ok_or(DKGError::<Bls12_381>::EvaluationDomainError)
             .unwrap();
-        let y_eval_i = domain.fft(&mut f);
+        let y_eval_i = domain.evaluate(&f);
         let f_i = f[1..=dkg_config.degree]
             .iter()
             .map(|a| dkg_config.srs.g_g1.mul(a.into_repr()).into_affine())
@@ -240,13 +231,26 @@ fn benchmark_with_signature_scheme<
             |b| {
                 b.iter(|| {
                     let mut node = Node {
-                        aggregator: DKGAggregator {
-                            config: dkg_config.clone(),
-                            scheme_pok: spok.clone(),
-                            scheme_sig: ssig.clone(),
-                            participants: participants.clone().into_iter().enumerate().collect(),
-                            transcript: DKGTranscript::empty(degree, num_participants),
-                        },
+                        aggregator: empty_aggregator.clone(),
+                        dealer: dealers[0].clone(),
+                    };
+                    node.receive_transcript_and_decrypt(rng, transcript.clone())
+                        .unwrap();
+                })
+            },
+        );
+
+        let mut small_scalars_aggregator = empty_aggregator.clone();
+        small_scalars_aggregator.batch_scalars = BatchScalars::Small(128);
+        c.bench_function(
+            &format!(
+                "huge-dkg(j={}, sig_scheme={}, nodes={}, degree={}) node receive transcript and decrypt with 128-bit batch scalars",
+                j, tag, num_nodes, degree
+            ),
+            |b| {
+                b.iter(|| {
+                    let mut node = Node {
+                        aggregator: small_scalars_aggregator.clone(),
                         dealer: dealers[0].clone(),
                     };
                     node.receive_transcript_and_decrypt(rng, transcript.clone())
@@ -0,0 +1,56 @@
+use aggregatable_dkg::signature::{
+    scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+    schnorr::{srs::SRS, SchnorrSignature},
+    utils::batch::BatchScalars,
+};
+use ark_bls12_381::G1Affine;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::thread_rng;
+
+const BATCH_SIZE: usize = 64;
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let rng = &mut thread_rng();
+    let srs = SRS::<G1Affine>::setup(rng).unwrap();
+    let schnorr = SchnorrSignature { srs };
+    let message = b"hello";
+
+    let keypairs = (0..BATCH_SIZE)
+        .map(|_| schnorr.generate_keypair(rng).unwrap())
+        .collect::<Vec<_>>();
+    let signatures = keypairs
+        .iter()
+        .map(|(sk, _)| schnorr.sign(rng, sk, &message[..]).unwrap())
+        .collect::<Vec<_>>();
+    let public_keys = keypairs.iter().map(|(_, pk)| pk).collect::<Vec<_>>();
+    let messages = vec![&message[..]; BATCH_SIZE];
+    let signatures = signatures.iter().collect::<Vec<_>>();
+
+    for (name, scalars) in &[
+        ("full", BatchScalars::Full),
+        ("small", BatchScalars::Small(128)),
+    ] {
+        c.bench_function(
+            &format!(
+                "schnorr batch verification of {} with {} scalars",
+                BATCH_SIZE, name
+            ),
+            |b| {
+                b.iter(|| {
+                    schnorr
+                        .batch_verify_with_scalars(
+                            rng,
+                            *scalars,
+                            &public_keys,
+                            &messages,
+                            &signatures,
+                        )
+                        .unwrap();
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
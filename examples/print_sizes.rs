@@ -7,22 +7,23 @@ use aggregatable_dkg::{
     dkg::{
         config::Config,
         dealer::Dealer,
+        domain::ParticipantDomain,
         errors::DKGError,
-        participant::{Participant, ParticipantState},
+        participant::{committee_digest, Participant, ParticipantState},
         pvss::PVSSShare,
-        share::{message_from_c_i, DKGTranscript, DKGTranscriptParticipant},
+        share::{message_from_c_i, ContributionPok, DKGTranscript, DKGTranscriptParticipant},
         srs::SRS as DKGSRS,
     },
     signature::{
         algebraic::{keypair::Keypair, srs::SRS},
         bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1, BLSSignatureG2},
-        scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+        scheme::{
+            BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme, SignatureScheme,
+        },
         schnorr::{srs::SRS as SchnorrSRS, SchnorrSignature},
     },
 };
-use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use rand::thread_rng;
-use std::marker::PhantomData;
 
 fn print_aggregatable_dkg_sizes() {
     let rng = &mut thread_rng();
@@ -75,7 +76,7 @@ fn print_bls_signature_sizes() {
 }
 
 fn print_transcript_size<
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = G2Affine, Secret = Fr>,
 >(
     num_nodes: usize,
@@ -88,20 +89,13 @@ fn print_transcript_size<
     let rng = &mut thread_rng();
 
     let u_1 = G2Projective::rand(rng).into_affine();
-    let dkg_config = Config {
-        srs: srs.clone(),
-        u_1,
-        degree,
-    };
 
     let mut dealers = vec![];
     for i in 0..num_nodes {
         let dealer_keypair_sig = ssig.generate_keypair(rng).unwrap();
         let participant = Participant {
-            pairing_type: PhantomData,
-            id: i,
-            public_key_sig: dealer_keypair_sig.1,
             state: ParticipantState::Dealer,
+            ..Participant::register(rng, &ssig, i, &dealer_keypair_sig.0).unwrap()
         };
         let dealer = Dealer::<Bls12_381, SSIG> {
             private_key_sig: dealer_keypair_sig.0,
@@ -116,6 +110,14 @@ fn print_transcript_size<
         .iter()
         .map(|d| d.participant.clone())
         .collect::<Vec<_>>();
+
+    let dkg_config = Config {
+        srs: srs.clone(),
+        u_1,
+        degree,
+        session_id: b"session".to_vec(),
+        committee_digest: committee_digest(&participants).unwrap(),
+    };
     let num_participants = participants.len();
 
     let mut transcript = DKGTranscript::<Bls12_381, SPOK, SSIG>::empty(degree, num_participants);
@@ -129,7 +131,7 @@ fn print_transcript_size<
             .sign(
                 rng,
                 &pok_keypair.0,
-                &message_from_c_i::<Bls12_381>(c.clone()).unwrap(),
+                &message_from_c_i(&dkg_config, c).unwrap(),
             )
             .unwrap();
 
@@ -138,14 +140,14 @@ fn print_transcript_size<
             .sign(
                 rng,
                 &signature_keypair.0,
-                &message_from_c_i::<Bls12_381>(c.clone()).unwrap(),
+                &message_from_c_i(&dkg_config, c).unwrap(),
             )
             .unwrap();
 
         let transcript_participant = DKGTranscriptParticipant::<Bls12_381, SPOK, SSIG> {
             c_i: c.clone(),
             weight: 1,
-            c_i_pok: pok,
+            c_i_pok: ContributionPok::Signature(pok),
             signature_on_c_i: signature,
         };
         accumulated_r += &r;
@@ -157,10 +159,10 @@ fn print_transcript_size<
         .map(|_| Fr::rand(rng))
         .collect::<Vec<_>>();
     f[0] = accumulated_r;
-    let domain = Radix2EvaluationDomain::<Fr>::new(participants.len())
+    let domain = ParticipantDomain::<Fr>::new(participants.len())
         .ok_or(DKGError::<Bls12_381>::EvaluationDomainError)
         .unwrap();
-    let y_eval_i = domain.fft(&mut f);
+    let y_eval_i = domain.evaluate(&f);
     let f_i = f[1..=dkg_config.degree]
         .iter()
         .map(|a| dkg_config.srs.g_g1.mul(a.into_repr()).into_affine())
@@ -197,6 +199,19 @@ fn print_transcript_size<
         tag,
         transcript_bytes.len()
     );
+
+    let mut aggregated_transcript_bytes = vec![];
+    transcript
+        .half_aggregate_poks(&dkg_config, &spok)
+        .unwrap()
+        .serialize(&mut aggregated_transcript_bytes)
+        .unwrap();
+    println!(
+        "Transcript size with half-aggregated POKs for participants={}, scheme={}: {}",
+        num_nodes,
+        tag,
+        aggregated_transcript_bytes.len()
+    );
 }
 
 fn main() {
@@ -1,71 +1,358 @@
 use crate::{
     dkg::{
+        cache::VerificationCache,
         config::Config,
+        domain::ParticipantDomain,
+        equivocation::{EquivocationPolicy, EquivocationProof},
         errors::DKGError,
-        participant::Participant,
-        pvss::PVSSShare,
-        share::{message_from_c_i, DKGShare, DKGTranscript, DKGTranscriptParticipant},
+        output::DKGOutput,
+        pairing::product_of_pairings_is_one,
+        participant::{committee_digest, verify_registrations, Participant},
+        prepared::PreparedG2,
+        pvss::{PVSSShare, PvssExactReport, PvssVerificationReport},
+        share::{
+            message_from_c_i, ContributionPok, DKGShare, DKGTranscript, DKGTranscriptParticipant,
+        },
+    },
+    signature::{
+        scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
+        utils::{
+            batch::{bisect_failures, find_invalid_signatures, BatchScalars},
+            hash::rng_from_message,
+        },
     },
-    signature::scheme::BatchVerifiableSignatureScheme,
 };
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{One, PrimeField, UniformRand, Zero};
-use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
-use ark_std::collections::BTreeMap;
-use rand::Rng;
-use std::ops::Neg;
+use ark_serialize::{CanonicalSerialize, SerializationError};
+use ark_std::collections::{BTreeMap, BTreeSet};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::ops::{Neg, Range};
+
+const CHALLENGE_PERSONALIZATION: &[u8] = b"DKGCHALL";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VerificationMode {
+    /// Fail with the error of the first batch check that fails.
+    Batch,
+    /// When the signature or POK batch check fails, find the participants whose signature or POK
+    /// is invalid and fail with `DKGError::InvalidContributions`.
+    Blame,
+}
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChallengeMode {
+    /// Draw the scalars that batch verification checks together from the caller's rng.
+    Rng,
+    /// Derive them from a hash of the config and of the whole statement being verified, so that
+    /// verification doesn't use the caller's rng and gives the same result everywhere.
+    FiatShamir,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TranscriptMerge {
+    /// The received transcript contains all the local contributions and more, and replaced the
+    /// local transcript.
+    Replaced,
+    /// The received transcript was aggregated into the local transcript.
+    Aggregated,
+    /// The local transcript already contains all the contributions of the received transcript.
+    Unchanged,
+}
+
+#[derive(Clone)]
 pub struct DKGAggregator<
     E: PairingEngine,
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
 > {
     pub config: Config<E>,
     pub scheme_pok: SPOK,
     pub scheme_sig: SSIG,
     pub participants: BTreeMap<usize, Participant<E, SSIG>>,
+    prepared: PreparedG2<E>,
 
     pub transcript: DKGTranscript<E, SPOK, SSIG>,
+
+    pub equivocation_policy: EquivocationPolicy,
+    pub equivocations: BTreeMap<usize, EquivocationProof<E, SSIG>>,
+
+    pub verification_mode: VerificationMode,
+    pub challenge_mode: ChallengeMode,
+    pub batch_scalars: BatchScalars,
+    pub verification_cache: VerificationCache<E, SPOK, SSIG>,
 }
 
 impl<
         E: PairingEngine,
-        SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
         SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
     > DKGAggregator<E, SPOK, SSIG>
 {
+    pub fn new<R: Rng>(
+        rng: &mut R,
+        config: Config<E>,
+        scheme_pok: SPOK,
+        scheme_sig: SSIG,
+        participants: BTreeMap<usize, Participant<E, SSIG>>,
+    ) -> Result<Self, DKGError<E>> {
+        let num_participants = participants.len();
+        for (participant_id, participant) in participants.iter() {
+            if *participant_id >= num_participants || participant.id != *participant_id {
+                return Err(DKGError::InvalidParticipantId(*participant_id));
+            }
+        }
+        if config.committee_digest != committee_digest(participants.values())? {
+            return Err(DKGError::CommitteeDigestMismatch);
+        }
+        verify_registrations(rng, &scheme_sig, &participants)?;
+        let degree = config.degree;
+        let prepared = PreparedG2::new(&config, &participants);
+        Ok(Self {
+            config,
+            scheme_pok,
+            scheme_sig,
+            participants,
+            prepared,
+            transcript: DKGTranscript::empty(degree, num_participants),
+            equivocation_policy: EquivocationPolicy::Record,
+            equivocations: BTreeMap::new(),
+            verification_mode: VerificationMode::Batch,
+            challenge_mode: ChallengeMode::Rng,
+            batch_scalars: BatchScalars::Full,
+            verification_cache: VerificationCache::new(),
+        })
+    }
+
     pub fn receive_share<R: Rng>(
         &mut self,
         rng: &mut R,
         share: &DKGShare<E, SPOK, SSIG>,
     ) -> Result<(), DKGError<E>> {
+        // Each participant contributes with weight 1 through its own share; more weight can only
+        // come from aggregating transcripts.
+        let participant_id = share.participant_id;
+        self.check_not_excluded(&[participant_id])?;
+        let existing = self
+            .transcript
+            .contributions
+            .get(&participant_id)
+            .map(|contribution| (contribution.c_i, contribution.signature_on_c_i.clone()));
+        if let Some((c_i, _)) = existing {
+            if c_i == share.c_i {
+                return Err(DKGError::DuplicateShare(participant_id));
+            }
+        }
         self.share_verify(rng, share)?;
+        if let Some((c_i, signature_on_c_i)) = existing {
+            self.equivocations
+                .entry(participant_id)
+                .or_insert(EquivocationProof {
+                    participant_id,
+                    c_i_1: c_i,
+                    signature_on_c_i_1: signature_on_c_i,
+                    c_i_2: share.c_i,
+                    signature_on_c_i_2: share.signature_on_c_i.clone(),
+                });
+            return Err(DKGError::ConflictingShare(participant_id));
+        }
+        self.add_share(share)
+    }
+
+    /// Verifies the shares together and aggregates the valid ones. The PVSS checks of all the
+    /// shares are combined into one MSM and one multi-pairing, and the signatures and POKs are
+    /// batch verified. If the batch fails, the invalid shares are found by bisection. Shares of
+    /// participants that already contributed go through `receive_share`. Fails with
+    /// `DKGError::RejectedShares` after aggregating the valid shares, if some were rejected.
+    pub fn receive_shares<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        shares: &[DKGShare<E, SPOK, SSIG>],
+    ) -> Result<(), DKGError<E>> {
+        let mut batch = vec![];
+        let mut batch_ids = BTreeSet::new();
+        let mut others = vec![];
+        let mut rejected = vec![];
+        for share in shares {
+            let participant_id = share.participant_id;
+            if self.transcript.contributions.contains_key(&participant_id)
+                || batch_ids.contains(&participant_id)
+            {
+                others.push(share);
+            } else if self.check_not_excluded(&[participant_id]).is_err()
+                || self.share_validate(share).is_err()
+            {
+                rejected.push(participant_id);
+            } else {
+                batch_ids.insert(participant_id);
+                batch.push(share);
+            }
+        }
+        let rng = &mut self.challenge_rng(rng, |bytes| {
+            batch
+                .iter()
+                .try_for_each(|share| share.serialize(&mut *bytes))
+        })?;
+
+        let invalid = if self.shares_are_correct(rng, &batch)? {
+            vec![]
+        } else {
+            bisect_failures(batch.len(), |range| {
+                self.shares_are_correct(rng, &batch[range])
+            })?
+        };
+        for (i, share) in batch.into_iter().enumerate() {
+            if invalid.contains(&i) {
+                rejected.push(share.participant_id);
+            } else {
+                self.add_share(share)?;
+            }
+        }
+        for share in others {
+            if self.receive_share(rng, share).is_err() {
+                rejected.push(share.participant_id);
+            }
+        }
+
+        if !rejected.is_empty() {
+            rejected.sort_unstable();
+            return Err(DKGError::RejectedShares(rejected));
+        }
+        Ok(())
+    }
+
+    // Aggregates a verified share.
+    fn add_share(&mut self, share: &DKGShare<E, SPOK, SSIG>) -> Result<(), DKGError<E>> {
+        let participant_id = share.participant_id;
         let transcript = DKGTranscript {
             degree: self.config.degree,
             num_participants: self.participants.len(),
             contributions: vec![(
-                share.participant_id,
+                participant_id,
                 DKGTranscriptParticipant {
                     c_i: share.c_i,
                     weight: 1,
-                    c_i_pok: share.c_i_pok.clone(),
+                    c_i_pok: ContributionPok::Signature(share.c_i_pok.clone()),
                     signature_on_c_i: share.signature_on_c_i.clone(),
                 },
             )]
             .into_iter()
             .collect(),
             pvss_share: share.pvss_share.clone(),
+            aggregated_pok: None,
         };
+        self.verification_cache.insert_share(&transcript);
         self.transcript = self.transcript.aggregate(&transcript)?;
         Ok(())
     }
 
+    pub fn decode_share(&self, bytes: &[u8]) -> Result<DKGShare<E, SPOK, SSIG>, DKGError<E>> {
+        DKGShare::decode(bytes, self.config.degree, self.participants.len())
+    }
+
+    pub fn decode_transcript(
+        &self,
+        bytes: &[u8],
+    ) -> Result<DKGTranscript<E, SPOK, SSIG>, DKGError<E>> {
+        DKGTranscript::decode(bytes, self.config.degree, self.participants.len())
+    }
+
     pub fn receive_transcript<R: Rng>(
         &mut self,
         rng: &mut R,
         transcript: &DKGTranscript<E, SPOK, SSIG>,
     ) -> Result<(), DKGError<E>> {
-        let mut c = E::G1Projective::zero();
+        let participant_ids = transcript.contributions.keys().copied().collect::<Vec<_>>();
+        self.check_not_excluded(&participant_ids)?;
+        self.transcript_verify(rng, transcript)?;
+        self.verification_cache.insert_transcript(transcript)?;
+
+        let equivocations = self.transcript.equivocations(transcript);
+        let equivocator_ids = equivocations
+            .iter()
+            .map(|proof| proof.participant_id)
+            .collect::<Vec<_>>();
+        for proof in equivocations {
+            self.equivocations
+                .entry(proof.participant_id)
+                .or_insert(proof);
+        }
+        self.check_not_excluded(&equivocator_ids)
+    }
+
+    /// Verifies the transcript and merges it into the local transcript. Returns whether and how
+    /// the local transcript changed, so that callers know when to gossip it again.
+    pub fn merge_transcript<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<TranscriptMerge, DKGError<E>> {
+        // Shares can't be added to a transcript with half-aggregated POKs, so it can't be merged.
+        if transcript.aggregated_pok.is_some() {
+            return Err(DKGError::AggregatedPoks);
+        }
+        self.receive_transcript(rng, transcript)?;
+        let contains = |a: &DKGTranscript<E, SPOK, SSIG>, b: &DKGTranscript<E, SPOK, SSIG>| {
+            b.contributions
+                .iter()
+                .all(|(participant_id, contribution)| {
+                    a.contributions.get(participant_id).map(|c| c.c_i) == Some(contribution.c_i)
+                })
+        };
+        if contains(&self.transcript, transcript) {
+            return Ok(TranscriptMerge::Unchanged);
+        }
+        if contains(transcript, &self.transcript) {
+            self.transcript = transcript.clone();
+            return Ok(TranscriptMerge::Replaced);
+        }
+        self.transcript = self.transcript.aggregate(transcript)?;
+        self.verification_cache
+            .insert_transcript(&self.transcript)?;
+        Ok(TranscriptMerge::Aggregated)
+    }
+
+    fn check_not_excluded(&self, participant_ids: &[usize]) -> Result<(), DKGError<E>> {
+        if self.equivocation_policy != EquivocationPolicy::Exclude {
+            return Ok(());
+        }
+        match participant_ids
+            .iter()
+            .find(|id| self.equivocations.contains_key(id))
+        {
+            Some(id) => Err(DKGError::ExcludedEquivocator(*id)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn output<R: Rng>(
+        &self,
+        rng: &mut R,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<DKGOutput<E>, DKGError<E>> {
+        self.transcript_verify(rng, transcript)?;
+        DKGOutput::from_verified_transcript(transcript)
+    }
+
+    pub fn transcript_verify<R: Rng>(
+        &self,
+        rng: &mut R,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<(), DKGError<E>> {
+        transcript.validate(self.config.degree, self.participants.len())?;
+        let rng = &mut self.challenge_rng(rng, |bytes| transcript.serialize(bytes))?;
+        // Contributions of cached transcripts and shares were already verified. Only check the
+        // others, and skip the PVSS checks.
+        let unverified = self
+            .verification_cache
+            .unverified_contributions(transcript)?;
+        let participant_ids = match &unverified {
+            Some(participant_ids) => participant_ids.clone(),
+            None => transcript.contributions.keys().copied().collect::<Vec<_>>(),
+        };
         let mut public_keys_sig = vec![];
         let mut messages_sig = vec![];
         let mut signatures_sig = vec![];
@@ -74,28 +361,30 @@ impl<
         let mut messages_pok = vec![];
         let mut signatures_pok = vec![];
 
-        for (participant_id, contribution) in transcript.contributions.iter() {
+        for participant_id in participant_ids.iter() {
+            let contribution = &transcript.contributions[participant_id];
             let participant = self
                 .participants
                 .get(participant_id)
                 .ok_or(DKGError::<E>::InvalidParticipantId(*participant_id))?;
-            let message = message_from_c_i(contribution.c_i)?;
+            let message = message_from_c_i(&self.config, contribution.c_i)?;
 
             public_keys_sig.push(&participant.public_key_sig);
             messages_sig.push(message.clone());
             signatures_sig.push(&contribution.signature_on_c_i);
 
-            public_keys_pok.push(&contribution.c_i);
-            messages_pok.push(message);
-            signatures_pok.push(&contribution.c_i_pok);
-
-            c += &contribution
-                .c_i
-                .mul(<E::Fr as From<u64>>::from(contribution.weight));
+            // Half-aggregated POKs are verified together below.
+            if let ContributionPok::Signature(signature) = &contribution.c_i_pok {
+                public_keys_pok.push(&contribution.c_i);
+                messages_pok.push(message);
+                signatures_pok.push(signature);
+            }
         }
         let sig_timer = start_timer!(|| "Signature batch verify");
-        self.scheme_sig.batch_verify(
+        let invalid_signatures = self.batch_verify_or_blame(
             rng,
+            &self.scheme_sig,
+            &participant_ids,
             &public_keys_sig,
             &messages_sig
                 .iter()
@@ -106,8 +395,10 @@ impl<
         end_timer!(sig_timer);
 
         let pok_timer = start_timer!(|| "POK batch verify");
-        self.scheme_pok.batch_verify(
+        let invalid_poks = self.batch_verify_or_blame(
             rng,
+            &self.scheme_pok,
+            &participant_ids,
             &public_keys_pok,
             &messages_pok
                 .iter()
@@ -115,111 +406,311 @@ impl<
                 .collect::<Vec<_>>(),
             &signatures_pok,
         )?;
+        let aggregated_pok_result = match &transcript.aggregated_pok {
+            Some(response) if !participant_ids.is_empty() => {
+                self.aggregated_pok_verify(transcript, response)
+            }
+            _ => Ok(()),
+        };
         end_timer!(pok_timer);
+        if !invalid_signatures.is_empty() || !invalid_poks.is_empty() {
+            return Err(DKGError::InvalidContributions(
+                invalid_signatures,
+                invalid_poks,
+            ));
+        }
+        aggregated_pok_result?;
 
+        if unverified.is_some() {
+            return Ok(());
+        }
+        let c =
+            transcript
+                .contributions
+                .values()
+                .fold(E::G1Projective::zero(), |acc, contribution| {
+                    acc + contribution
+                        .c_i
+                        .mul(<E::Fr as From<u64>>::from(contribution.weight))
+                });
         let pvss_timer = start_timer!(|| "PVSS share verify");
         self.pvss_share_verify(rng, c.into_affine(), &transcript.pvss_share)?;
         end_timer!(pvss_timer);
         Ok(())
     }
 
+    // Verifies the half-aggregated POK of all the contributions of the transcript, with one check
+    // that can't tell which POK is invalid.
+    fn aggregated_pok_verify(
+        &self,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+        response: &SPOK::Response,
+    ) -> Result<(), DKGError<E>> {
+        let mut public_keys = vec![];
+        let mut messages = vec![];
+        let mut commitments = vec![];
+        for contribution in transcript.contributions.values() {
+            match &contribution.c_i_pok {
+                ContributionPok::Aggregated(commitment) => commitments.push(commitment),
+                ContributionPok::Signature(_) => return Err(DKGError::InvalidAggregatedPok),
+            }
+            public_keys.push(&contribution.c_i);
+            messages.push(message_from_c_i(&self.config, contribution.c_i)?);
+        }
+        self.scheme_pok
+            .verify_half_aggregate(
+                &public_keys,
+                &messages.iter().map(|v| v.as_slice()).collect::<Vec<_>>(),
+                &commitments,
+                response,
+            )
+            .map_err(|_| DKGError::InvalidAggregatedPok)
+    }
+
+    // Returns the rng that the batching scalars of a verification are drawn from. In `FiatShamir`
+    // mode, it is seeded with a hash of the config and of the statement written by
+    // `write_statement`, and `rng` isn't used. The committee digest in the config covers the
+    // participants' keys.
+    fn challenge_rng<R: Rng, F: FnOnce(&mut Vec<u8>) -> Result<(), SerializationError>>(
+        &self,
+        rng: &mut R,
+        write_statement: F,
+    ) -> Result<ChaChaRng, DKGError<E>> {
+        if self.challenge_mode == ChallengeMode::Rng {
+            return Ok(ChaChaRng::from_seed(rng.gen()));
+        }
+        let mut bytes = vec![];
+        self.config.srs.g_g1.serialize(&mut bytes)?;
+        self.config.srs.h_g2.serialize(&mut bytes)?;
+        self.config.u_1.serialize(&mut bytes)?;
+        self.config.degree.serialize(&mut bytes)?;
+        self.config.session_id.serialize(&mut bytes)?;
+        self.config.committee_digest.serialize(&mut bytes)?;
+        write_statement(&mut bytes)?;
+        Ok(rng_from_message(CHALLENGE_PERSONALIZATION, &bytes))
+    }
+
+    /// Returns the ids of the participants whose signatures are invalid. In `Batch` mode, fails
+    /// instead if the batch check fails.
+    fn batch_verify_or_blame<R: Rng, S: BatchVerifiableSignatureScheme>(
+        &self,
+        rng: &mut R,
+        scheme: &S,
+        participant_ids: &[usize],
+        public_keys: &[&S::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&S::Signature],
+    ) -> Result<Vec<usize>, DKGError<E>> {
+        if public_keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let batch_error = match scheme.batch_verify_with_scalars(
+            rng,
+            self.batch_scalars,
+            public_keys,
+            messages,
+            signatures,
+        ) {
+            Ok(()) => return Ok(vec![]),
+            Err(e) => e,
+        };
+        if self.verification_mode == VerificationMode::Batch {
+            return Err(batch_error.into());
+        }
+        let invalid = find_invalid_signatures(
+            scheme,
+            rng,
+            self.batch_scalars,
+            public_keys,
+            messages,
+            signatures,
+        )?;
+        if invalid.is_empty() {
+            return Err(batch_error.into());
+        }
+        Ok(invalid.into_iter().map(|i| participant_ids[i]).collect())
+    }
+
     pub fn pvss_share_verify<R: Rng>(
         &self,
         rng: &mut R,
         c_i: E::G1Affine,
         share: &PVSSShare<E>,
     ) -> Result<(), DKGError<E>> {
-        // Verify evaluations are correct probabilistically.
-        let alpha = E::Fr::rand(rng);
-        let domain = Radix2EvaluationDomain::<E::Fr>::new(self.participants.len())
-            .ok_or(DKGError::<E>::EvaluationDomainError)?;
-        let lagrange_coefficients = domain
-            .evaluate_all_lagrange_coefficients(alpha)
-            .into_iter()
-            .map(|c| c.into_repr())
-            .collect::<Vec<_>>();
-
-        {
-            let mut bases = vec![];
-            let mut scalars = vec![];
-            bases.extend_from_slice(&share.a_i);
-            scalars.extend_from_slice(&lagrange_coefficients);
-            let powers_of_alpha = {
-                let mut current_alpha = E::Fr::one().neg();
-                let mut powers = vec![];
-                for _ in 0..=self.config.degree {
-                    powers.push(current_alpha.into_repr());
-                    current_alpha *= &alpha;
-                }
-                powers
-            };
-            bases.extend_from_slice(&[vec![c_i], share.f_i.clone()].concat());
-            scalars.extend_from_slice(&powers_of_alpha);
-            let product = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
-            if !product.is_zero() {
-                return Err(DKGError::EvaluationsCheckError(product.into()));
-            }
+        let report = self.pvss_share_report(rng, c_i, share)?;
+        if !report.is_valid() {
+            return Err(DKGError::InvalidPVSSShare(report));
         }
+        Ok(())
+    }
+
+    /// Runs all the checks of `pvss_share_verify`. If the encryption check fails, the recipients
+    /// with bad ciphertexts are found by bisection.
+    pub fn pvss_share_report<R: Rng>(
+        &self,
+        rng: &mut R,
+        c_i: E::G1Affine,
+        share: &PVSSShare<E>,
+    ) -> Result<PvssVerificationReport<E>, DKGError<E>> {
+        let num_recipients = self.participants.len();
+        share.validate(self.config.degree, num_recipients)?;
+        let rng = &mut self.challenge_rng(rng, |bytes| {
+            c_i.serialize(&mut *bytes)?;
+            share.serialize(bytes)
+        })?;
+        let evaluations_correct = self.evaluations_are_correct(rng, c_i, share)?;
 
         // Verify same ratio. Need this for security proof.
         let pairs = [
-            (c_i.into(), self.config.u_1.into()),
+            (c_i.into(), self.prepared.u_1.clone()),
             (self.config.srs.g_g1.neg().into(), share.u_i_2.into()),
         ];
-        if !E::product_of_pairings(pairs.iter()).is_one() {
-            return Err(DKGError::RatioIncorrect);
+        let ratio_correct = E::product_of_pairings(pairs.iter()).is_one();
+
+        let encryption_correct = self.encryptions_are_correct(rng, share, 0..num_recipients)?;
+        let invalid_recipients = if encryption_correct {
+            vec![]
+        } else {
+            bisect_failures(num_recipients, |range| {
+                self.encryptions_are_correct(rng, share, range)
+            })?
+        };
+
+        Ok(PvssVerificationReport {
+            c_i,
+            evaluations_correct,
+            ratio_correct,
+            encryption_correct,
+            invalid_recipients,
+        })
+    }
+
+    /// A slow reference for `pvss_share_report`, for audits and differential testing. Recomputes
+    /// each `a_i` from `c_i` and `f_i` in the exponent, and checks every pairing equation on its
+    /// own, without randomness or batching.
+    pub fn pvss_share_report_exact(
+        &self,
+        c_i: E::G1Affine,
+        share: &PVSSShare<E>,
+    ) -> Result<PvssExactReport<E>, DKGError<E>> {
+        let num_participants = self.participants.len();
+        share.validate(self.config.degree, num_participants)?;
+        let domain = ParticipantDomain::<E::Fr>::new(num_participants)
+            .ok_or(DKGError::<E>::EvaluationDomainError)?;
+        let g_g1 = self.config.srs.g_g1;
+
+        let ratio_correct = E::pairing(c_i, self.config.u_1) == E::pairing(g_g1, share.u_i_2);
+
+        let bases = [vec![c_i], share.f_i.clone()].concat();
+        let mut evaluations_correct = vec![];
+        let mut encryptions_correct = vec![];
+        for (i, (a, y)) in share.a_i.iter().zip(share.y_i.iter()).enumerate() {
+            let point = domain.element(i);
+            let mut power = E::Fr::one();
+            let mut scalars = vec![];
+            for _ in 0..bases.len() {
+                scalars.push(power.into_repr());
+                power *= &point;
+            }
+            let evaluation = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+            evaluations_correct.push(evaluation.into_affine() == *a);
+
+            let participant = self
+                .participants
+                .get(&i)
+                .ok_or(DKGError::<E>::InvalidParticipantId(i))?;
+            encryptions_correct
+                .push(E::pairing(*a, participant.public_key_sig) == E::pairing(g_g1, *y));
         }
 
+        Ok(PvssExactReport {
+            c_i,
+            ratio_correct,
+            evaluations_correct,
+            encryptions_correct,
+        })
+    }
+
+    // Verify evaluations are correct probabilistically.
+    fn evaluations_are_correct<R: Rng>(
+        &self,
+        rng: &mut R,
+        c_i: E::G1Affine,
+        share: &PVSSShare<E>,
+    ) -> Result<bool, DKGError<E>> {
+        let alpha = E::Fr::rand(rng);
+        let domain = ParticipantDomain::<E::Fr>::new(self.participants.len())
+            .ok_or(DKGError::<E>::EvaluationDomainError)?;
+        let lagrange_coefficients = domain
+            .lagrange_coefficients(alpha)
+            .into_iter()
+            .map(|c| c.into_repr())
+            .collect::<Vec<_>>();
+
+        let mut bases = vec![];
+        let mut scalars = vec![];
+        bases.extend_from_slice(&share.a_i);
+        scalars.extend_from_slice(&lagrange_coefficients);
         let powers_of_alpha = {
-            let mut current_alpha = E::Fr::one();
+            let mut current_alpha = E::Fr::one().neg();
             let mut powers = vec![];
-            for _ in 0..=self.participants.len() {
+            for _ in 0..=self.config.degree {
                 powers.push(current_alpha.into_repr());
                 current_alpha *= &alpha;
             }
             powers
         };
+        bases.extend_from_slice(&[vec![c_i], share.f_i.clone()].concat());
+        scalars.extend_from_slice(&powers_of_alpha);
+        let product = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+        Ok(product.is_zero())
+    }
+
+    // Verify evaluations of the recipients in `recipients` are encrypted correctly.
+    fn encryptions_are_correct<R: Rng>(
+        &self,
+        rng: &mut R,
+        share: &PVSSShare<E>,
+        recipients: Range<usize>,
+    ) -> Result<bool, DKGError<E>> {
+        let powers_of_alpha = self
+            .batch_scalars
+            .sample::<E::Fr, _>(rng, recipients.len())
+            .into_iter()
+            .map(|weight| weight.into_repr())
+            .collect::<Vec<_>>();
         let (batched_a_i, batched_g_1_neg) = {
             let g_1_neg = self.config.srs.g_g1.neg();
-            let batched_a_i = share
-                .a_i
-                .iter()
-                .zip(powers_of_alpha.iter())
+            let batched_a_i = cfg_iter!(share.a_i[recipients.clone()])
+                .zip(cfg_iter!(powers_of_alpha))
                 .map(|(a, power)| a.mul(*power))
                 .collect::<Vec<_>>();
-            let batched_g_1_neg = powers_of_alpha
-                .iter()
+            let batched_g_1_neg = cfg_iter!(powers_of_alpha)
                 .map(|power| g_1_neg.mul(*power))
                 .collect::<Vec<_>>();
             let mut batched_all = vec![];
             batched_all.extend_from_slice(&batched_a_i);
             batched_all.extend_from_slice(&batched_g_1_neg);
             let batched_all = E::G1Projective::batch_normalization_into_affine(&batched_all);
-            let batched_a_i = batched_all[..batched_a_i.len()]
-                .into_iter()
-                .map(|x| x.clone())
-                .collect::<Vec<_>>();
-            let batched_g_1_neg = batched_all[batched_a_i.len()..]
-                .into_iter()
-                .map(|x| x.clone())
-                .collect::<Vec<_>>();
+            let batched_a_i = batched_all[..batched_a_i.len()].to_vec();
+            let batched_g_1_neg = batched_all[batched_a_i.len()..].to_vec();
             (batched_a_i, batched_g_1_neg)
         };
-        // Verify evaluations are encrypted correctly.
         let pairs = batched_a_i
             .into_iter()
-            .zip(share.y_i.iter())
+            .zip(share.y_i[recipients.clone()].iter())
             .zip(batched_g_1_neg.into_iter())
-            .enumerate()
+            .zip(recipients)
             .map::<Result<Vec<(E::G1Prepared, E::G2Prepared)>, DKGError<E>>, _>(
-                |(i, ((a, y), g_1_neg))| {
-                    let participant = self
-                        .participants
-                        .get(&i)
+                |(((a, y), g_1_neg), i)| {
+                    let public_key_sig = self
+                        .prepared
+                        .public_keys_sig
+                        .get(i)
                         .ok_or(DKGError::<E>::InvalidParticipantId(i))?;
                     let pairs = vec![
                         (g_1_neg.into(), (*y).into()),
-                        (a.into(), participant.public_key_sig.into()),
+                        (a.into(), public_key_sig.clone()),
                     ];
 
                     Ok(pairs)
@@ -229,10 +720,7 @@ impl<
             .into_iter()
             .flatten()
             .collect::<Vec<(E::G1Prepared, E::G2Prepared)>>();
-        if !E::product_of_pairings(pairs.iter()).is_one() {
-            return Err(DKGError::RatioIncorrect);
-        }
-        Ok(())
+        Ok(product_of_pairings_is_one::<E>(&pairs))
     }
 
     pub fn share_verify<R: Rng>(
@@ -240,24 +728,170 @@ impl<
         rng: &mut R,
         share: &DKGShare<E, SPOK, SSIG>,
     ) -> Result<(), DKGError<E>> {
-        let participant_id = share.participant_id;
-        let participant = self
-            .participants
-            .get(&participant_id)
-            .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))?;
-
+        self.share_validate(share)?;
         self.pvss_share_verify(rng, share.c_i, &share.pvss_share)?;
+        let participant = &self.participants[&share.participant_id];
         // Verify signature on C_i by participant i.
         self.scheme_sig.verify(
             &participant.public_key_sig,
-            &message_from_c_i(share.c_i)?,
+            &message_from_c_i(&self.config, share.c_i)?,
             &share.signature_on_c_i,
         )?;
 
         // Verify POK of C_i.
-        self.scheme_pok
-            .verify(&share.c_i, &message_from_c_i(share.c_i)?, &share.c_i_pok)?;
+        self.scheme_pok.verify(
+            &share.c_i,
+            &message_from_c_i(&self.config, share.c_i)?,
+            &share.c_i_pok,
+        )?;
 
         Ok(())
     }
+
+    // Checks that the share is well formed, before any cryptographic check.
+    fn share_validate(&self, share: &DKGShare<E, SPOK, SSIG>) -> Result<(), DKGError<E>> {
+        let participant_id = share.participant_id;
+        if !self.participants.contains_key(&participant_id) {
+            return Err(DKGError::InvalidParticipantId(participant_id));
+        }
+        if share.c_i.is_zero() {
+            return Err(DKGError::IdentityCommitment(participant_id));
+        }
+        share
+            .pvss_share
+            .validate(self.config.degree, self.participants.len())?;
+        if share.pvss_share.u_i_2.is_zero() {
+            return Err(DKGError::IdentityElement("u_i_2"));
+        }
+        Ok(())
+    }
+
+    // Runs all the checks of `share_verify` on well formed shares at once. Each PVSS check is
+    // weighted by a fresh random scalar, so that the evaluation checks add up to one MSM and the
+    // ratio and encryption checks to one multi-pairing. `rng` is the challenge rng of the batch.
+    fn shares_are_correct<R: Rng>(
+        &self,
+        rng: &mut R,
+        shares: &[&DKGShare<E, SPOK, SSIG>],
+    ) -> Result<bool, DKGError<E>> {
+        if shares.is_empty() {
+            return Ok(true);
+        }
+        let num_participants = self.participants.len();
+        let domain = ParticipantDomain::<E::Fr>::new(num_participants)
+            .ok_or(DKGError::<E>::EvaluationDomainError)?;
+        let alpha = E::Fr::rand(rng);
+        let lagrange_coefficients = domain.lagrange_coefficients(alpha);
+        let powers_of_alpha = {
+            let mut current_alpha = E::Fr::one().neg();
+            let mut powers = vec![];
+            for _ in 0..=self.config.degree {
+                powers.push(current_alpha);
+                current_alpha *= &alpha;
+            }
+            powers
+        };
+
+        let mut evaluation_bases = vec![];
+        let mut evaluation_scalars = vec![];
+        let mut g2_bases = vec![];
+        let mut g2_scalars = vec![];
+        let mut batched_c_i = E::G1Projective::zero();
+        let mut a_i_scalars = vec![vec![]; num_participants];
+        let weights = self
+            .batch_scalars
+            .sample::<E::Fr, _>(rng, shares.len() * (num_participants + 2));
+        for (share, weights) in shares.iter().zip(weights.chunks(num_participants + 2)) {
+            let pvss_share = &share.pvss_share;
+            let r = weights[0];
+            evaluation_bases.extend_from_slice(&pvss_share.a_i);
+            evaluation_scalars.extend(lagrange_coefficients.iter().map(|l| (*l * r).into_repr()));
+            evaluation_bases.push(share.c_i);
+            evaluation_bases.extend_from_slice(&pvss_share.f_i);
+            evaluation_scalars.extend(powers_of_alpha.iter().map(|p| (*p * r).into_repr()));
+
+            let r = weights[1];
+            batched_c_i += &share.c_i.mul(r.into_repr());
+            g2_bases.push(pvss_share.u_i_2);
+            g2_scalars.push(r.into_repr());
+
+            for ((scalars, y), r) in a_i_scalars
+                .iter_mut()
+                .zip(pvss_share.y_i.iter())
+                .zip(weights[2..].iter())
+            {
+                scalars.push(r.into_repr());
+                g2_bases.push(*y);
+                g2_scalars.push(r.into_repr());
+            }
+        }
+        let evaluations_correct =
+            VariableBaseMSM::multi_scalar_mul(&evaluation_bases, &evaluation_scalars).is_zero();
+
+        let batched_g2 = VariableBaseMSM::multi_scalar_mul(&g2_bases, &g2_scalars);
+        let a_i_bases = (0..num_participants)
+            .map(|i| {
+                shares
+                    .iter()
+                    .map(|share| share.pvss_share.a_i[i])
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let batched_a_i = cfg_iter!(a_i_bases)
+            .zip(cfg_iter!(a_i_scalars))
+            .map(|(bases, scalars)| VariableBaseMSM::multi_scalar_mul(bases, scalars))
+            .collect::<Vec<_>>();
+        let batched_a_i = E::G1Projective::batch_normalization_into_affine(&batched_a_i);
+        let mut pairs = vec![
+            (batched_c_i.into_affine().into(), self.prepared.u_1.clone()),
+            (
+                self.config.srs.g_g1.neg().into(),
+                batched_g2.into_affine().into(),
+            ),
+        ];
+        for (a, public_key_sig) in batched_a_i
+            .into_iter()
+            .zip(self.prepared.public_keys_sig.iter())
+        {
+            pairs.push((a.into(), public_key_sig.clone()));
+        }
+        let pairings_correct = product_of_pairings_is_one::<E>(&pairs);
+
+        let messages = shares
+            .iter()
+            .map(|share| message_from_c_i(&self.config, share.c_i))
+            .collect::<Result<Vec<_>, _>>()?;
+        let messages = messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+        let signatures_correct = self
+            .scheme_sig
+            .batch_verify_with_scalars(
+                rng,
+                self.batch_scalars,
+                &shares
+                    .iter()
+                    .map(|share| &self.participants[&share.participant_id].public_key_sig)
+                    .collect::<Vec<_>>(),
+                &messages,
+                &shares
+                    .iter()
+                    .map(|share| &share.signature_on_c_i)
+                    .collect::<Vec<_>>(),
+            )
+            .is_ok();
+        let poks_correct = self
+            .scheme_pok
+            .batch_verify_with_scalars(
+                rng,
+                self.batch_scalars,
+                &shares.iter().map(|share| &share.c_i).collect::<Vec<_>>(),
+                &messages,
+                &shares
+                    .iter()
+                    .map(|share| &share.c_i_pok)
+                    .collect::<Vec<_>>(),
+            )
+            .is_ok();
+
+        Ok(evaluations_correct && pairings_correct && signatures_correct && poks_correct)
+    }
 }
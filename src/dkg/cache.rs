@@ -0,0 +1,222 @@
+use crate::{
+    dkg::{errors::DKGError, share::DKGTranscript},
+    signature::scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
+};
+use ark_ec::PairingEngine;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::collections::BTreeMap;
+use blake2s_simd::Params;
+use std::collections::VecDeque;
+
+const PERSONALIZATION: &[u8] = b"DKGVCACH";
+const TRANSCRIPT_CAPACITY: usize = 16;
+
+type ContributionSet = Vec<(usize, u64)>;
+
+/// Transcripts and shares that passed verification. The PVSS checks are linear, so a transcript
+/// that is the sum of a verified transcript and verified shares is valid, and only the signatures
+/// and POKs it doesn't share with them need to be checked.
+#[derive(Clone)]
+pub struct VerificationCache<
+    E: PairingEngine,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+> {
+    /// Verified transcripts, keyed by their contribution set and digest.
+    transcripts: BTreeMap<(ContributionSet, Vec<u8>), DKGTranscript<E, SPOK, SSIG>>,
+    insertion_order: VecDeque<(ContributionSet, Vec<u8>)>,
+    /// Verified shares, as single-contribution transcripts keyed by participant id.
+    shares: BTreeMap<usize, DKGTranscript<E, SPOK, SSIG>>,
+}
+
+impl<
+        E: PairingEngine,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    > VerificationCache<E, SPOK, SSIG>
+{
+    pub fn new() -> Self {
+        Self {
+            transcripts: BTreeMap::new(),
+            insertion_order: VecDeque::new(),
+            shares: BTreeMap::new(),
+        }
+    }
+
+    /// Records a verified share, given as a transcript with the share as its only contribution.
+    /// Only the first share of each participant is kept.
+    pub fn insert_share(&mut self, transcript: &DKGTranscript<E, SPOK, SSIG>) {
+        if let Some(participant_id) = transcript.contributions.keys().next() {
+            self.shares
+                .entry(*participant_id)
+                .or_insert_with(|| transcript.clone());
+        }
+    }
+
+    /// Records a verified transcript, evicting the oldest one when the cache is full. Transcripts
+    /// with half-aggregated POKs are skipped, as shares can't be added to them.
+    pub fn insert_transcript(
+        &mut self,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<(), DKGError<E>> {
+        if transcript.aggregated_pok.is_some() {
+            return Ok(());
+        }
+        let key = Self::key(transcript)?;
+        if self.transcripts.contains_key(&key) {
+            return Ok(());
+        }
+        if self.insertion_order.len() == TRANSCRIPT_CAPACITY {
+            if let Some(oldest) = self.insertion_order.pop_front() {
+                self.transcripts.remove(&oldest);
+            }
+        }
+        self.insertion_order.push_back(key.clone());
+        self.transcripts.insert(key, transcript.clone());
+        Ok(())
+    }
+
+    /// If the transcript is a verified transcript plus verified shares, returns the ids of the
+    /// contributions whose signature or POK differs from the verified ones. Returns `None` if the
+    /// transcript has to be fully verified.
+    pub fn unverified_contributions(
+        &self,
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<Option<Vec<usize>>, DKGError<E>> {
+        if self.transcripts.contains_key(&Self::key(transcript)?) {
+            return Ok(Some(vec![]));
+        }
+        let empty = DKGTranscript::empty(transcript.degree, transcript.num_participants);
+        let mut bases = self.transcripts.values().collect::<Vec<_>>();
+        bases.sort_by_key(|base| std::cmp::Reverse(base.contributions.len()));
+        bases.push(&empty);
+
+        let mut pvss_share_bytes = vec![];
+        transcript.pvss_share.serialize(&mut pvss_share_bytes)?;
+        // Composing is only worth it while it costs less than a plain verification, counted over
+        // all the bases so that a transcript can't make every base expensive to compose.
+        let mut budget =
+            plain_verification_cost::<E>(transcript.degree, transcript.num_participants);
+        for base in bases {
+            let candidate = match self.compose(base, transcript, &mut budget)? {
+                Some(candidate) => candidate,
+                None => continue,
+            };
+            let mut candidate_bytes = vec![];
+            candidate.pvss_share.serialize(&mut candidate_bytes)?;
+            if candidate_bytes != pvss_share_bytes {
+                continue;
+            }
+            let mut unverified = vec![];
+            for (participant_id, contribution) in transcript.contributions.iter() {
+                let verified = &candidate.contributions[participant_id];
+                if !same_bytes(&contribution.c_i_pok, &verified.c_i_pok)?
+                    || !same_bytes(&contribution.signature_on_c_i, &verified.signature_on_c_i)?
+                {
+                    unverified.push(*participant_id);
+                }
+            }
+            return Ok(Some(unverified));
+        }
+        Ok(None)
+    }
+
+    // Adds to `base` the verified shares needed to reach the contributions of `target`, each share
+    // once and scaled by its missing weight, if there are such shares and their cost fits in
+    // `budget`.
+    fn compose(
+        &self,
+        base: &DKGTranscript<E, SPOK, SSIG>,
+        target: &DKGTranscript<E, SPOK, SSIG>,
+        budget: &mut u64,
+    ) -> Result<Option<DKGTranscript<E, SPOK, SSIG>>, DKGError<E>> {
+        for (participant_id, contribution) in base.contributions.iter() {
+            match target.contributions.get(participant_id) {
+                Some(c) if c.c_i == contribution.c_i && c.weight >= contribution.weight => {}
+                _ => return Ok(None),
+            }
+        }
+        let mut candidate = base.clone();
+        for (participant_id, contribution) in target.contributions.iter() {
+            let base_weight = base
+                .contributions
+                .get(participant_id)
+                .map_or(0, |c| c.weight);
+            let missing_weight = contribution.weight - base_weight;
+            if missing_weight == 0 {
+                continue;
+            }
+            if missing_weight > target.num_participants as u64 {
+                return Ok(None);
+            }
+            let share = match self.shares.get(participant_id) {
+                Some(share) if share.contributions[participant_id].c_i == contribution.c_i => share,
+                _ => return Ok(None),
+            };
+            // Scaling a point takes up to two group operations per bit of the weight, and adding
+            // it to the candidate one more.
+            let num_points = (target.degree + 2 * target.num_participants + 1) as u64;
+            let weight_bits = 64 - missing_weight.leading_zeros() as u64;
+            let cost = num_points * (2 * weight_bits + 1);
+            if cost > *budget {
+                *budget = 0;
+                return Ok(None);
+            }
+            *budget -= cost;
+            let mut scaled = share.clone();
+            scaled.pvss_share = share.pvss_share.scale(missing_weight);
+            if let Some(scaled_contribution) = scaled.contributions.get_mut(participant_id) {
+                scaled_contribution.weight = missing_weight;
+            }
+            candidate = candidate.aggregate(&scaled)?;
+        }
+        Ok(Some(candidate))
+    }
+
+    fn key(
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<(ContributionSet, Vec<u8>), DKGError<E>> {
+        let contribution_set = transcript
+            .contributions
+            .iter()
+            .map(|(participant_id, contribution)| (*participant_id, contribution.weight))
+            .collect();
+        let mut bytes = vec![];
+        transcript.serialize(&mut bytes)?;
+        let hash = Params::new()
+            .hash_length(32)
+            .personal(PERSONALIZATION)
+            .to_state()
+            .update(&bytes)
+            .finalize();
+        Ok((contribution_set, hash.as_bytes().to_vec()))
+    }
+}
+
+impl<
+        E: PairingEngine,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    > Default for VerificationCache<E, SPOK, SSIG>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A lower bound on the group operations of a plain PVSS verification: the evaluation check is a
+// multi-scalar multiplication with `degree + 1` full-width scalars per participant, and each
+// participant adds pairings that cost more than a full-width scalar multiplication each.
+fn plain_verification_cost<E: PairingEngine>(degree: usize, num_participants: usize) -> u64 {
+    let scalar_bits = <E::Fr as PrimeField>::size_in_bits() as u64;
+    num_participants as u64 * (degree as u64 + 1 + 2 * scalar_bits)
+}
+
+fn same_bytes<E: PairingEngine, T: CanonicalSerialize>(a: &T, b: &T) -> Result<bool, DKGError<E>> {
+    let mut a_bytes = vec![];
+    a.serialize(&mut a_bytes)?;
+    let mut b_bytes = vec![];
+    b.serialize(&mut b_bytes)?;
+    Ok(a_bytes == b_bytes)
+}
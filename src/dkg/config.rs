@@ -6,4 +6,9 @@ pub struct Config<E: PairingEngine> {
     pub srs: SRS<E>,
     pub u_1: E::G2Affine,
     pub degree: usize,
+    /// Identifies the DKG run, e.g. the epoch. Bound into every signed message, together with
+    /// `committee_digest`, so that signatures can't be replayed into another run.
+    pub session_id: Vec<u8>,
+    /// See `participant::committee_digest`.
+    pub committee_digest: Vec<u8>,
 }
@@ -0,0 +1,41 @@
+use crate::dkg::errors::DKGError;
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, Read};
+
+/// Reads a value of a DKG wire type from untrusted bytes. Points are read with the checked
+/// `CanonicalDeserialize::deserialize`, which rejects points that are not on the curve or not in
+/// the prime order subgroup.
+pub fn read<E: PairingEngine, T: CanonicalDeserialize, R: Read>(
+    reader: &mut R,
+    field: &'static str,
+) -> Result<T, DKGError<E>> {
+    T::deserialize(reader).map_err(|e| DKGError::DecodeError(field, e))
+}
+
+pub fn read_len<E: PairingEngine, R: Read>(
+    reader: &mut R,
+    field: &'static str,
+) -> Result<usize, DKGError<E>> {
+    let len: u64 = read(reader, field)?;
+    Ok(len as usize)
+}
+
+/// Reads a vector, checking its length before reading any element.
+pub fn read_vec<E: PairingEngine, T: CanonicalDeserialize, R: Read>(
+    reader: &mut R,
+    field: &'static str,
+    expected_len: usize,
+) -> Result<Vec<T>, DKGError<E>> {
+    let len = read_len(reader, field)?;
+    if len != expected_len {
+        return Err(DKGError::WrongLength(field, expected_len, len));
+    }
+    (0..len).map(|_| read(reader, field)).collect()
+}
+
+pub fn ensure_consumed<E: PairingEngine>(bytes: &[u8]) -> Result<(), DKGError<E>> {
+    if !bytes.is_empty() {
+        return Err(DKGError::TrailingBytes(bytes.len()));
+    }
+    Ok(())
+}
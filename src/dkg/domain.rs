@@ -0,0 +1,149 @@
+use ark_ff::{batch_inversion, FftField};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+
+/// Evaluation points of the participants. Participant `i` is assigned `omega^i` in the smallest
+/// radix-2 domain that holds all participants, so shares are still computed with an FFT when
+/// the number of participants is not a power of two; the remaining points of the domain are
+/// simply not used.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticipantDomain<F: FftField> {
+    pub domain: Radix2EvaluationDomain<F>,
+    pub num_participants: usize,
+}
+
+impl<F: FftField> ParticipantDomain<F> {
+    pub fn new(num_participants: usize) -> Option<Self> {
+        if num_participants == 0 {
+            return None;
+        }
+        let domain = Radix2EvaluationDomain::<F>::new(num_participants)?;
+        Some(Self {
+            domain,
+            num_participants,
+        })
+    }
+
+    pub fn element(&self, participant_id: usize) -> F {
+        self.domain.element(participant_id)
+    }
+
+    /// Evaluates the polynomial with the given coefficients at the points of all participants.
+    pub fn evaluate(&self, coeffs: &[F]) -> Vec<F> {
+        let mut evaluations = self.domain.fft(coeffs);
+        evaluations.truncate(self.num_participants);
+        evaluations
+    }
+
+    /// Evaluates the Lagrange basis polynomials of the participants' points at `tau`.
+    pub fn lagrange_coefficients(&self, tau: F) -> Vec<F> {
+        let n = self.num_participants;
+        if n == self.domain.size() {
+            return self.domain.evaluate_all_lagrange_coefficients(tau);
+        }
+
+        let points = (0..n).map(|i| self.element(i)).collect::<Vec<_>>();
+        if let Some(k) = points.iter().position(|x| *x == tau) {
+            let mut coefficients = vec![F::zero(); n];
+            coefficients[k] = F::one();
+            return coefficients;
+        }
+
+        // L_i(tau) = Z(tau) / ((tau - omega^i) * d_i), where Z vanishes on the participants'
+        // points and d_i = prod_{j != i} (omega^i - omega^j). Factoring omega^i out of each term
+        // gives d_i = omega^{i(n - 1)} * prod_{k=1}^{i} (1 - omega^{-k}) *
+        // prod_{k=1}^{n-1-i} (1 - omega^k), which only needs prefix products.
+        let omega = self.domain.group_gen;
+        let omega_inv = self.domain.group_gen_inv;
+        let mut prefix = vec![F::one(); n];
+        let mut prefix_inv = vec![F::one(); n];
+        let (mut omega_k, mut omega_inv_k) = (omega, omega_inv);
+        for k in 1..n {
+            prefix[k] = prefix[k - 1] * (F::one() - omega_k);
+            prefix_inv[k] = prefix_inv[k - 1] * (F::one() - omega_inv_k);
+            omega_k *= omega;
+            omega_inv_k *= omega_inv;
+        }
+
+        let omega_n_minus_1 = omega.pow([(n - 1) as u64]);
+        let mut current = F::one();
+        let mut denominators = Vec::with_capacity(n);
+        for i in 0..n {
+            denominators.push((tau - points[i]) * current * prefix_inv[i] * prefix[n - 1 - i]);
+            current *= omega_n_minus_1;
+        }
+        batch_inversion(&mut denominators);
+
+        let vanishing = points.iter().fold(F::one(), |acc, x| acc * (tau - x));
+        denominators.into_iter().map(|d| d * vanishing).collect()
+    }
+
+    /// Evaluates at zero the Lagrange basis polynomials of the given participants' points.
+    pub fn lagrange_coefficients_at_zero(&self, participant_ids: &[usize]) -> Vec<F> {
+        let points = participant_ids
+            .iter()
+            .map(|i| self.element(*i))
+            .collect::<Vec<_>>();
+        // lambda_i = prod_{j != i} x_j / (x_j - x_i)
+        let mut numerators = vec![F::one(); points.len()];
+        let mut denominators = vec![F::one(); points.len()];
+        for (i, x_i) in points.iter().enumerate() {
+            for (j, x_j) in points.iter().enumerate() {
+                if i != j {
+                    numerators[i] *= x_j;
+                    denominators[i] *= *x_j - x_i;
+                }
+            }
+        }
+        batch_inversion(&mut denominators);
+        numerators
+            .into_iter()
+            .zip(denominators)
+            .map(|(n, d)| n * d)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ParticipantDomain;
+    use ark_bls12_381::Fr;
+    use ark_ff::{One, UniformRand, Zero};
+    use rand::thread_rng;
+
+    fn evaluate(coeffs: &[Fr], x: Fr) -> Fr {
+        coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+    }
+
+    #[test]
+    fn test_evaluate_and_interpolate() {
+        let rng = &mut thread_rng();
+        for &n in [1, 2, 3, 4, 5, 7, 8, 100].iter() {
+            let domain = ParticipantDomain::<Fr>::new(n).unwrap();
+            let coeffs = (0..n).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+            let evaluations = domain.evaluate(&coeffs);
+            assert_eq!(evaluations.len(), n);
+            for (i, e) in evaluations.iter().enumerate() {
+                assert_eq!(*e, evaluate(&coeffs, domain.element(i)));
+            }
+
+            let tau = Fr::rand(rng);
+            let interpolated = domain
+                .lagrange_coefficients(tau)
+                .into_iter()
+                .zip(evaluations.iter())
+                .fold(Fr::zero(), |acc, (l, e)| acc + l * e);
+            assert_eq!(interpolated, evaluate(&coeffs, tau));
+
+            let at_point = domain.lagrange_coefficients(domain.element(n - 1));
+            assert_eq!(at_point[n - 1], Fr::one());
+
+            let ids = (0..n).rev().collect::<Vec<_>>();
+            let interpolated_at_zero = domain
+                .lagrange_coefficients_at_zero(&ids)
+                .into_iter()
+                .zip(ids.iter())
+                .fold(Fr::zero(), |acc, (l, i)| acc + l * evaluations[*i]);
+            assert_eq!(interpolated_at_zero, coeffs[0]);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{
+    dkg::{config::Config, errors::DKGError, share::message_from_c_i},
+    signature::scheme::BatchVerifiableSignatureScheme,
+};
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EquivocationPolicy {
+    /// Keep a proof of the equivocation, but keep aggregating the participant's contributions.
+    Record,
+    /// Keep a proof of the equivocation and reject any later share or transcript containing a
+    /// contribution of the participant. Contributions aggregated before the equivocation was
+    /// detected stay in the aggregated transcript.
+    Exclude,
+}
+
+/// Two different commitments signed by the same participant.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct EquivocationProof<
+    E: PairingEngine,
+    SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+> {
+    pub participant_id: usize,
+    pub c_i_1: E::G1Affine,
+    pub signature_on_c_i_1: SSIG::Signature,
+    pub c_i_2: E::G1Affine,
+    pub signature_on_c_i_2: SSIG::Signature,
+}
+
+impl<
+        E: PairingEngine,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    > EquivocationProof<E, SSIG>
+{
+    pub fn verify(
+        &self,
+        config: &Config<E>,
+        scheme_sig: &SSIG,
+        public_key_sig: &SSIG::PublicKey,
+    ) -> Result<(), DKGError<E>> {
+        if self.c_i_1 == self.c_i_2 {
+            return Err(DKGError::InvalidEquivocationProof(self.participant_id));
+        }
+        scheme_sig.verify(
+            public_key_sig,
+            &message_from_c_i(config, self.c_i_1)?,
+            &self.signature_on_c_i_1,
+        )?;
+        scheme_sig.verify(
+            public_key_sig,
+            &message_from_c_i(config, self.c_i_2)?,
+            &self.signature_on_c_i_2,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::EquivocationProof;
+    use crate::{
+        dkg::{config::Config, share::message_from_c_i, srs::SRS},
+        signature::{
+            bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1},
+            scheme::SignatureScheme,
+        },
+    };
+    use ark_bls12_381::{Bls12_381, G1Projective, G2Projective};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::UniformRand;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_equivocation_proof() {
+        let rng = &mut thread_rng();
+        let bls_sig = BLSSignature::<BLSSignatureG1<Bls12_381>> {
+            srs: BLSSRS {
+                g_public_key: G2Projective::rand(rng).into_affine(),
+                g_signature: G1Projective::rand(rng).into_affine(),
+            },
+        };
+        let (sk, pk) = bls_sig.generate_keypair(rng).unwrap();
+        let (_, other_pk) = bls_sig.generate_keypair(rng).unwrap();
+        let config = Config {
+            srs: SRS::<Bls12_381>::setup(rng).unwrap(),
+            u_1: G2Projective::rand(rng).into_affine(),
+            degree: 1,
+            session_id: b"session".to_vec(),
+            committee_digest: vec![],
+        };
+
+        let c_i_1 = G1Projective::rand(rng).into_affine();
+        let c_i_2 = G1Projective::rand(rng).into_affine();
+        let sign = |c_i| {
+            bls_sig
+                .sign(
+                    &mut thread_rng(),
+                    &sk,
+                    &message_from_c_i(&config, c_i).unwrap(),
+                )
+                .unwrap()
+        };
+        let proof = EquivocationProof::<Bls12_381, BLSSignature<BLSSignatureG1<Bls12_381>>> {
+            participant_id: 0,
+            c_i_1,
+            signature_on_c_i_1: sign(c_i_1),
+            c_i_2,
+            signature_on_c_i_2: sign(c_i_2),
+        };
+        proof.verify(&config, &bls_sig, &pk).unwrap();
+        proof.verify(&config, &bls_sig, &other_pk).unwrap_err();
+
+        let mut serialized = vec![];
+        proof.serialize(&mut serialized).unwrap();
+        let deserialized =
+            EquivocationProof::<Bls12_381, BLSSignature<BLSSignatureG1<Bls12_381>>>::deserialize(
+                &serialized[..],
+            )
+            .unwrap();
+        deserialized.verify(&config, &bls_sig, &pk).unwrap();
+
+        let same_commitment =
+            EquivocationProof::<Bls12_381, BLSSignature<BLSSignatureG1<Bls12_381>>> {
+                c_i_2: c_i_1,
+                signature_on_c_i_2: sign(c_i_1),
+                ..proof
+            };
+        same_commitment.verify(&config, &bls_sig, &pk).unwrap_err();
+    }
+}
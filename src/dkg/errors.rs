@@ -1,14 +1,12 @@
-use crate::signature::utils::errors::SignatureError;
+use crate::{dkg::pvss::PvssVerificationReport, signature::utils::errors::SignatureError};
 use ark_ec::PairingEngine;
 use ark_serialize::SerializationError;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum DKGError<E: PairingEngine> {
-    #[error("Ratio incorrect")]
-    RatioIncorrect,
-    #[error("Evaluations are wrong: product = {0}")]
-    EvaluationsCheckError(E::G1Affine),
+    #[error("Invalid PVSS share: {0:?}")]
+    InvalidPVSSShare(PvssVerificationReport<E>),
     #[error("Could not generate evaluation domain")]
     EvaluationDomainError,
     #[error("Config, dealer and nodes had different SRSes")]
@@ -21,6 +19,50 @@ pub enum DKGError<E: PairingEngine> {
     InvalidParticipantId(usize),
     #[error("Transcripts have different degree or number of participants: self.degree={0}, other.degree={1}, self.num_participants={2}, self.num_participants={3}")]
     TranscriptDifferentConfig(usize, usize, usize, usize),
-    #[error("Transcripts have different commitments")]
-    TranscriptDifferentCommitments,
+    #[error("Transcript doesn't match the config: degree={0}, expected degree={1}, num_participants={2}, expected num_participants={3}")]
+    TranscriptConfigMismatch(usize, usize, usize, usize),
+    #[error("Wrong number of elements in {0}: expected {1}, got {2}")]
+    WrongLength(&'static str, usize, usize),
+    #[error("{0} is the identity")]
+    IdentityElement(&'static str),
+    #[error("Commitment of participant {0} is the identity")]
+    IdentityCommitment(usize),
+    #[error("Contribution of participant {0} has zero weight")]
+    ZeroWeight(usize),
+    #[error("Contribution of participant {0} has a weight above the maximum")]
+    WeightTooLarge(usize),
+    #[error("Could not decode {0}: {1}")]
+    DecodeError(&'static str, SerializationError),
+    #[error("Too many elements in {0}: at most {1}, got {2}")]
+    TooManyElements(&'static str, usize, usize),
+    #[error("{0} bytes left after decoding")]
+    TrailingBytes(usize),
+    #[error("Transcript has no contributions")]
+    EmptyTranscript,
+    #[error("Transcripts have different commitments for participant {0}")]
+    TranscriptDifferentCommitments(usize),
+    #[error("Participant {0} already contributed this share")]
+    DuplicateShare(usize),
+    #[error("Participant {0} already contributed a different share")]
+    ConflictingShare(usize),
+    #[error("Invalid equivocation proof for participant {0}")]
+    InvalidEquivocationProof(usize),
+    #[error("Participant {0} equivocated and is excluded")]
+    ExcludedEquivocator(usize),
+    #[error("Committee digest in config doesn't match the participants")]
+    CommitteeDigestMismatch,
+    #[error("Participant {0} has a zero or out-of-subgroup public key")]
+    InvalidPublicKey(usize),
+    #[error("Participant {0} has an invalid proof of possession")]
+    InvalidProofOfPossession(usize),
+    #[error("Invalid signatures from participants {0:?}, invalid POKs from participants {1:?}")]
+    InvalidContributions(Vec<usize>, Vec<usize>),
+    #[error("Rejected shares from participants {0:?}")]
+    RejectedShares(Vec<usize>),
+    #[error("POK of participant {0} is aggregated in a transcript without aggregated POKs, or the reverse")]
+    InconsistentPok(usize),
+    #[error("Transcripts with half-aggregated POKs can't be aggregated again")]
+    AggregatedPoks,
+    #[error("Invalid half-aggregated POK")]
+    InvalidAggregatedPok,
 }
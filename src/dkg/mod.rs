@@ -1,9 +1,16 @@
 pub mod aggregator;
+pub mod cache;
 pub mod config;
 pub mod dealer;
+pub mod decode;
+pub mod domain;
+pub mod equivocation;
 pub mod errors;
 pub mod node;
+pub mod output;
+pub mod pairing;
 pub mod participant;
+pub mod prepared;
 pub mod pvss;
 pub mod share;
 pub mod srs;
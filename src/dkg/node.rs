@@ -3,22 +3,25 @@ use crate::{
         aggregator::DKGAggregator,
         config::Config,
         dealer::Dealer,
+        domain::ParticipantDomain,
         errors::DKGError,
         participant::{Participant, ParticipantState},
         pvss::{PVSSShare, PVSSShareSecrets},
         share::{message_from_c_i, DKGShare, DKGTranscript},
     },
-    signature::scheme::BatchVerifiableSignatureScheme,
+    signature::scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
 };
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{Field, PrimeField, UniformRand};
-use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use rand::Rng;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::BTreeMap;
 
+#[derive(Clone)]
 pub struct Node<
     E: PairingEngine,
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
 > {
     pub aggregator: DKGAggregator<E, SPOK, SSIG>,
@@ -27,27 +30,20 @@ pub struct Node<
 
 impl<
         E: PairingEngine,
-        SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
         SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
     > Node<E, SPOK, SSIG>
 {
-    pub fn new(
+    pub fn new<R: Rng>(
+        rng: &mut R,
         config: Config<E>,
         scheme_pok: SPOK,
         scheme_sig: SSIG,
         dealer: Dealer<E, SSIG>,
         participants: BTreeMap<usize, Participant<E, SSIG>>,
     ) -> Result<Self, DKGError<E>> {
-        let degree = config.degree;
-        let num_participants = participants.len();
         let node = Node {
-            aggregator: DKGAggregator {
-                config,
-                scheme_pok,
-                scheme_sig,
-                participants,
-                transcript: DKGTranscript::empty(degree, num_participants),
-            },
+            aggregator: DKGAggregator::new(rng, config, scheme_pok, scheme_sig, participants)?,
             dealer,
         };
         Ok(node)
@@ -57,23 +53,25 @@ impl<
         &mut self,
         rng: &mut R,
     ) -> Result<(PVSSShare<E>, PVSSShareSecrets<E>), DKGError<E>> {
-        let mut f = (0..=self.aggregator.config.degree)
+        let f = (0..=self.aggregator.config.degree)
             .map(|_| E::Fr::rand(rng))
             .collect::<Vec<_>>();
-        let domain = Radix2EvaluationDomain::<E::Fr>::new(self.aggregator.participants.len())
+        let domain = ParticipantDomain::<E::Fr>::new(self.aggregator.participants.len())
             .ok_or(DKGError::<E>::EvaluationDomainError)?;
-        let y_eval_i = domain.fft(&mut f);
-
-        let f_i = f[1..=self.aggregator.config.degree]
-            .iter()
-            .map(|a| {
+        let y_eval_i = domain.evaluate(&f);
+        let g_g1 = self.aggregator.config.srs.g_g1;
+        let public_keys_sig = (0..y_eval_i.len())
+            .map(|i| {
                 self.aggregator
-                    .config
-                    .srs
-                    .g_g1
-                    .mul(a.into_repr())
-                    .into_affine()
+                    .participants
+                    .get(&i)
+                    .map(|participant| participant.public_key_sig)
+                    .ok_or(DKGError::<E>::InvalidParticipantId(i))
             })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let f_i = cfg_iter!(f[1..=self.aggregator.config.degree])
+            .map(|a| g_g1.mul(a.into_repr()).into_affine())
             .collect::<Vec<_>>();
         let u_i_2 = self
             .aggregator
@@ -81,31 +79,13 @@ impl<
             .u_1
             .mul(f[0].into_repr())
             .into_affine();
-        let a_i = y_eval_i
-            .iter()
-            .map(|a| {
-                self.aggregator
-                    .config
-                    .srs
-                    .g_g1
-                    .mul(a.into_repr())
-                    .into_affine()
-            })
+        let a_i = cfg_iter!(y_eval_i)
+            .map(|a| g_g1.mul(a.into_repr()).into_affine())
+            .collect::<Vec<_>>();
+        let y_i = cfg_iter!(y_eval_i)
+            .zip(cfg_iter!(public_keys_sig))
+            .map(|(a, public_key_sig)| public_key_sig.mul(a.into_repr()).into_affine())
             .collect::<Vec<_>>();
-        let y_i = y_eval_i
-            .iter()
-            .enumerate()
-            .map::<Result<E::G2Affine, DKGError<E>>, _>(|(i, a)| {
-                Ok(self
-                    .aggregator
-                    .participants
-                    .get(&i)
-                    .ok_or(DKGError::<E>::InvalidParticipantId(i))?
-                    .public_key_sig
-                    .mul(a.into_repr())
-                    .into_affine())
-            })
-            .collect::<Result<_, _>>()?;
         let pvss_share = PVSSShare {
             f_i,
             u_i_2,
@@ -143,19 +123,21 @@ impl<
             .aggregator
             .scheme_pok
             .from_sk(&pvss_share_secrets.f_0)?;
-        let pok = self
-            .aggregator
-            .scheme_pok
-            .sign(rng, &pok_keypair.0, &message_from_c_i(c_i)?)?;
+        let pok = self.aggregator.scheme_pok.sign(
+            rng,
+            &pok_keypair.0,
+            &message_from_c_i(&self.aggregator.config, c_i)?,
+        )?;
 
         let signature_keypair = self
             .aggregator
             .scheme_sig
             .from_sk(&(self.dealer.private_key_sig))?;
-        let signature =
-            self.aggregator
-                .scheme_sig
-                .sign(rng, &signature_keypair.0, &message_from_c_i(c_i)?)?;
+        let signature = self.aggregator.scheme_sig.sign(
+            rng,
+            &signature_keypair.0,
+            &message_from_c_i(&self.aggregator.config, c_i)?,
+        )?;
 
         let share = DKGShare {
             participant_id: self.dealer.participant.id,
@@ -170,58 +152,90 @@ impl<
     }
 
     // Assumes that the participant id has been authenticated.
+    // Returns whether the share was adopted. A share from a participant whose contribution is
+    // already in the adopted transcript is ignored, and an invalid or conflicting share is an
+    // error.
     pub fn receive_share_and_decrypt<R: Rng>(
         &mut self,
         rng: &mut R,
         share: DKGShare<E, SPOK, SSIG>,
-    ) -> Result<(), DKGError<E>> {
+    ) -> Result<bool, DKGError<E>> {
         let participant_id = share.participant_id;
-
-        match (|| -> Result<E::G2Affine, DKGError<E>> {
-            self.aggregator.receive_share(rng, &share)?;
-
-            let secret = share.pvss_share.y_i[self.dealer.participant.id]
-                .mul(self.dealer.private_key_sig.inverse().unwrap().into_repr())
-                .into_affine();
-
-            Ok(secret)
-        })() {
-            Ok(secret) => {
-                self.dealer.accumulated_secret = self.dealer.accumulated_secret + secret;
-                let participant = self
-                    .aggregator
-                    .participants
-                    .get_mut(&participant_id)
-                    .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))?;
-                participant.state = ParticipantState::Verified;
+        if let Some(contribution) = self
+            .aggregator
+            .transcript
+            .contributions
+            .get(&participant_id)
+        {
+            if contribution.c_i == share.c_i {
+                return Ok(false);
             }
-            Err(_) => {}
-        };
+        }
 
-        Ok(())
+        match self.aggregator.receive_share(rng, &share) {
+            Err(DKGError::DuplicateShare(_)) => return Ok(false),
+            result => result?,
+        }
+        self.decrypt_secret()?;
+        let participant = self
+            .aggregator
+            .participants
+            .get_mut(&participant_id)
+            .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))?;
+        participant.state = ParticipantState::Verified;
+
+        Ok(true)
     }
 
     // Assumes that the participant id has been authenticated.
+    // Returns whether the transcript was adopted. A transcript replaces the adopted one if it has
+    // more contributors, or as many contributors and more total weight. Re-delivering the adopted
+    // transcript, or delivering a worse one, is a no-op.
     pub fn receive_transcript_and_decrypt<R: Rng>(
         &mut self,
         rng: &mut R,
         transcript: DKGTranscript<E, SPOK, SSIG>,
-    ) -> Result<(), DKGError<E>> {
+    ) -> Result<bool, DKGError<E>> {
+        // Shares can't be added to a transcript with half-aggregated POKs, so it can't be adopted.
+        if transcript.aggregated_pok.is_some() {
+            return Err(DKGError::AggregatedPoks);
+        }
         self.aggregator.receive_transcript(rng, &transcript)?;
 
-        let secret = transcript.pvss_share.y_i[self.dealer.participant.id]
-            .mul(self.dealer.private_key_sig.inverse().unwrap().into_repr())
-            .into_affine();
+        let adopted = &self.aggregator.transcript;
+        let is_better = (transcript.contributions.len(), transcript.total_weight())
+            > (adopted.contributions.len(), adopted.total_weight());
+        if !is_better {
+            return Ok(false);
+        }
 
-        for (participant_id, _) in transcript.contributions {
+        for participant_id in transcript.contributions.keys() {
             let participant = self
                 .aggregator
                 .participants
-                .get_mut(&participant_id)
-                .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))?;
+                .get_mut(participant_id)
+                .ok_or(DKGError::<E>::InvalidParticipantId(*participant_id))?;
             participant.state = ParticipantState::Verified;
         }
-        self.dealer.accumulated_secret = self.dealer.accumulated_secret + secret;
+        self.aggregator.transcript = transcript;
+        self.decrypt_secret()?;
+
+        Ok(true)
+    }
+
+    // The secret is always derived from the adopted transcript, so that it can't drift from it.
+    fn decrypt_secret(&mut self) -> Result<(), DKGError<E>> {
+        let participant_id = self.dealer.participant.id;
+        let y_i = self
+            .aggregator
+            .transcript
+            .pvss_share
+            .y_i
+            .get(participant_id)
+            .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))?;
+        self.dealer.accumulated_secret = y_i
+            .mul(self.dealer.private_key_sig.inverse().unwrap().into_repr())
+            .into_affine();
 
         Ok(())
     }
@@ -231,26 +245,31 @@ impl<
 mod test {
     use crate::{
         dkg::{
-            aggregator::DKGAggregator,
+            aggregator::{ChallengeMode, DKGAggregator, TranscriptMerge, VerificationMode},
             config::Config,
             dealer::Dealer,
+            domain::ParticipantDomain,
+            equivocation::EquivocationPolicy,
+            errors::DKGError,
             node::Node,
-            participant::{Participant, ParticipantState},
-            share::DKGTranscript,
+            participant::{committee_digest, Participant, ParticipantState},
+            share::{DKGTranscript, MAX_CONTRIBUTION_WEIGHT},
             srs::SRS,
         },
         signature::{
             bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1, BLSSignatureG2},
-            scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+            scheme::{
+                BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme, SignatureScheme,
+            },
             schnorr::{srs::SRS as SchnorrSRS, SchnorrSignature},
+            utils::batch::BatchScalars,
         },
     };
     use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
-    use ark_ec::ProjectiveCurve;
-    use ark_ff::{UniformRand, Zero};
-    use rand::thread_rng;
-
-    use std::marker::PhantomData;
+    use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+    use ark_ff::{PrimeField, UniformRand, Zero};
+    use ark_serialize::CanonicalSerialize;
+    use rand::{thread_rng, RngCore};
 
     #[test]
     fn test_one() {
@@ -273,32 +292,32 @@ mod test {
             private_key_sig: dealer_keypair_sig.0,
             accumulated_secret: G2Projective::zero().into_affine(),
             participant: Participant {
-                pairing_type: PhantomData,
-                id: 0,
-                public_key_sig: dealer_keypair_sig.1,
                 state: ParticipantState::Dealer,
+                ..Participant::register(rng, &bls_sig, 0, &dealer_keypair_sig.0).unwrap()
             },
         };
 
         let u_1 = G2Projective::rand(rng).into_affine();
+
+        let participants = vec![dealer.participant.clone()];
+
         let dkg_config = Config {
             srs: srs.clone(),
             u_1,
             degree: 10,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(&participants).unwrap(),
         };
 
-        let participants = vec![dealer.participant.clone()];
-        let degree = dkg_config.degree;
-        let num_participants = participants.len();
-
         let mut node = Node {
-            aggregator: DKGAggregator {
-                config: dkg_config.clone(),
-                scheme_pok: bls_pok.clone(),
-                scheme_sig: bls_sig.clone(),
-                participants: participants.clone().into_iter().enumerate().collect(),
-                transcript: DKGTranscript::empty(degree, num_participants),
-            },
+            aggregator: DKGAggregator::new(
+                rng,
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap(),
             dealer,
         };
 
@@ -325,20 +344,13 @@ mod test {
         };
 
         let u_1 = G2Projective::rand(rng).into_affine();
-        let dkg_config = Config {
-            srs: srs.clone(),
-            u_1,
-            degree: 2,
-        };
 
         let mut dealers = vec![];
         for i in 0..NODES {
             let dealer_keypair_sig = bls_sig.generate_keypair(rng).unwrap();
             let participant = Participant {
-                pairing_type: PhantomData,
-                id: i,
-                public_key_sig: dealer_keypair_sig.1,
                 state: ParticipantState::Dealer,
+                ..Participant::register(rng, &bls_sig, i, &dealer_keypair_sig.0).unwrap()
             };
             let dealer = Dealer {
                 private_key_sig: dealer_keypair_sig.0,
@@ -353,30 +365,1280 @@ mod test {
             .iter()
             .map(|d| d.participant.clone())
             .collect::<Vec<_>>();
+
+        let dkg_config = Config {
+            srs: srs.clone(),
+            u_1,
+            degree: 2,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(&participants).unwrap(),
+        };
         let mut nodes = vec![];
-        for i in 0..NODES {
-            let degree = dkg_config.degree;
-            let num_participants = participants.len();
+        for dealer in dealers.iter() {
             let node = Node {
-                aggregator: DKGAggregator {
-                    config: dkg_config.clone(),
-                    scheme_pok: bls_pok.clone(),
-                    scheme_sig: bls_sig.clone(),
-                    participants: participants.clone().into_iter().enumerate().collect(),
-                    transcript: DKGTranscript::empty(degree, num_participants),
-                },
-                dealer: dealers[i].clone(),
+                aggregator: DKGAggregator::new(
+                    rng,
+                    dkg_config.clone(),
+                    bls_pok.clone(),
+                    bls_sig.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap(),
+                dealer: dealer.clone(),
             };
             nodes.push(node);
         }
         for i in 0..NODES {
-            let node = &mut nodes[i];
+            let share = nodes[i].share(rng).unwrap();
+            for node in nodes.iter_mut() {
+                node.receive_share_and_decrypt(rng, share.clone()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_3_nodes() {
+        test_num_nodes(3, 1, 3);
+    }
+
+    #[test]
+    fn test_5_nodes() {
+        test_num_nodes(5, 3, 5);
+    }
+
+    #[test]
+    fn test_7_nodes() {
+        test_num_nodes(7, 4, 7);
+    }
+
+    #[test]
+    fn test_100_nodes() {
+        test_num_nodes(100, 66, 2);
+    }
+
+    type PokScheme = BLSSignature<BLSSignatureG2<Bls12_381>>;
+    type SigScheme = BLSSignature<BLSSignatureG1<Bls12_381>>;
+
+    fn setup_bls(
+        num_nodes: usize,
+        degree: usize,
+    ) -> (
+        Config<Bls12_381>,
+        PokScheme,
+        SigScheme,
+        Vec<Dealer<Bls12_381, SigScheme>>,
+    ) {
+        let rng = &mut thread_rng();
+        let srs = SRS::<Bls12_381>::setup(rng).unwrap();
+        let bls_sig = BLSSignature::<BLSSignatureG1<Bls12_381>> {
+            srs: BLSSRS {
+                g_public_key: srs.h_g2,
+                g_signature: srs.g_g1,
+            },
+        };
+        let bls_pok = BLSSignature::<BLSSignatureG2<Bls12_381>> {
+            srs: BLSSRS {
+                g_public_key: srs.g_g1,
+                g_signature: srs.h_g2,
+            },
+        };
+
+        let u_1 = G2Projective::rand(rng).into_affine();
+        let mut dealers = vec![];
+        for i in 0..num_nodes {
+            let dealer_keypair_sig = bls_sig.generate_keypair(rng).unwrap();
+            let participant = Participant {
+                state: ParticipantState::Dealer,
+                ..Participant::register(rng, &bls_sig, i, &dealer_keypair_sig.0).unwrap()
+            };
+            let dealer = Dealer {
+                private_key_sig: dealer_keypair_sig.0,
+                accumulated_secret: G2Projective::zero().into_affine(),
+                participant,
+            };
+
+            dealers.push(dealer);
+        }
+
+        let dkg_config = Config {
+            srs,
+            u_1,
+            degree,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(dealers.iter().map(|d| &d.participant)).unwrap(),
+        };
+
+        (dkg_config, bls_pok, bls_sig, dealers)
+    }
+
+    fn out_of_subgroup_g2_point() -> G2Affine {
+        let rng = &mut thread_rng();
+        loop {
+            let mut bytes = [0u8; 96];
+            rng.fill_bytes(&mut bytes);
+            if let Some(p) = G2Affine::from_random_bytes(&bytes) {
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    return p;
+                }
+            }
+        }
+    }
+
+    fn test_num_nodes(num_nodes: usize, degree: usize, num_sharing: usize) {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(num_nodes, degree);
+        let srs = dkg_config.srs.clone();
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+
+        let mut aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            bls_pok.clone(),
+            bls_sig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        for dealer in dealers[..num_sharing].iter() {
+            let share = new_node(dealer).share(rng).unwrap();
+            assert_eq!(share.pvss_share.a_i.len(), num_nodes);
+            assert_eq!(share.pvss_share.y_i.len(), num_nodes);
+            aggregator.receive_share(rng, &share).unwrap();
+        }
+
+        let transcript = aggregator.transcript.clone();
+        let output = aggregator.output(rng, &transcript).unwrap();
+        assert_eq!(output.participant_public_keys.len(), num_nodes);
+        for &i in [0, num_nodes / 2, num_nodes - 1].iter() {
+            let mut node = new_node(&dealers[i]);
+            node.receive_transcript_and_decrypt(rng, transcript.clone())
+                .unwrap();
+            assert_eq!(
+                Bls12_381::pairing(output.participant_public_key(i).unwrap(), srs.h_g2),
+                Bls12_381::pairing(srs.g_g1, node.dealer.accumulated_secret)
+            );
+        }
+
+        let domain = ParticipantDomain::<Fr>::new(num_nodes).unwrap();
+        let ids = (num_nodes - degree - 1..num_nodes).collect::<Vec<_>>();
+        let group_public_key = domain
+            .lagrange_coefficients_at_zero(&ids)
+            .into_iter()
+            .zip(ids.iter())
+            .fold(G1Projective::zero(), |acc, (l, i)| {
+                acc + output.participant_public_keys[*i].mul(l.into_repr())
+            });
+        assert_eq!(group_public_key.into_affine(), output.group_public_key);
+    }
+
+    #[test]
+    fn test_overlapping_delivery() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+
+        let shares = dealers
+            .iter()
+            .map(|d| new_node(d).share(rng).unwrap())
+            .collect::<Vec<_>>();
+        let mut transcripts = vec![];
+        for ids in [vec![1, 2], vec![0, 1, 2]].iter() {
+            let mut aggregator = new_node(&dealers[0]).aggregator;
+            for id in ids.iter() {
+                aggregator.receive_share(rng, &shares[*id]).unwrap();
+            }
+            transcripts.push(aggregator.transcript);
+        }
+
+        let mut node = new_node(&dealers[3]);
+        assert!(node
+            .receive_share_and_decrypt(rng, shares[0].clone())
+            .unwrap());
+        assert!(node
+            .receive_share_and_decrypt(rng, shares[1].clone())
+            .unwrap());
+        let secret = node.dealer.accumulated_secret;
+
+        // Overlaps with the adopted contributions without being better.
+        assert!(!node
+            .receive_transcript_and_decrypt(rng, transcripts[0].clone())
+            .unwrap());
+        assert_eq!(node.dealer.accumulated_secret, secret);
+
+        // Overlaps with the adopted contributions and is better, so it replaces them.
+        assert!(node
+            .receive_transcript_and_decrypt(rng, transcripts[1].clone())
+            .unwrap());
+        let mut other_node = new_node(&dealers[3]);
+        assert!(other_node
+            .receive_transcript_and_decrypt(rng, transcripts[1].clone())
+            .unwrap());
+        assert_eq!(
+            node.dealer.accumulated_secret,
+            other_node.dealer.accumulated_secret
+        );
+
+        // Already adopted.
+        assert!(!node
+            .receive_transcript_and_decrypt(rng, transcripts[1].clone())
+            .unwrap());
+        assert!(!node
+            .receive_share_and_decrypt(rng, shares[2].clone())
+            .unwrap());
+        assert_eq!(
+            node.dealer.accumulated_secret,
+            other_node.dealer.accumulated_secret
+        );
+
+        assert!(node
+            .receive_share_and_decrypt(rng, shares[3].clone())
+            .unwrap());
+        assert!(other_node
+            .receive_share_and_decrypt(rng, shares[3].clone())
+            .unwrap());
+        assert_eq!(
+            node.dealer.accumulated_secret,
+            other_node.dealer.accumulated_secret
+        );
+    }
+
+    #[test]
+    fn test_equivocation() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(3, 1);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+
+        // Participant 0 signs two different commitments.
+        let mut equivocating_node = new_node(&dealers[0]);
+        let share_0_a = equivocating_node.share(rng).unwrap();
+        let share_0_b = equivocating_node.share(rng).unwrap();
+        let share_1 = new_node(&dealers[1]).share(rng).unwrap();
+        let share_2 = new_node(&dealers[2]).share(rng).unwrap();
+
+        let mut aggregator_a = new_node(&dealers[1]).aggregator;
+        aggregator_a.receive_share(rng, &share_0_a).unwrap();
+        aggregator_a.receive_share(rng, &share_1).unwrap();
+        let mut aggregator_b = new_node(&dealers[2]).aggregator;
+        aggregator_b.receive_share(rng, &share_0_b).unwrap();
+        aggregator_b.receive_share(rng, &share_2).unwrap();
+
+        match aggregator_a.transcript.aggregate(&aggregator_b.transcript) {
+            Err(DKGError::TranscriptDifferentCommitments(0)) => {}
+            _ => panic!("should have found different commitments"),
+        }
+        let equivocations = aggregator_a
+            .transcript
+            .equivocations(&aggregator_b.transcript);
+        assert_eq!(equivocations.len(), 1);
+        assert_eq!(equivocations[0].participant_id, 0);
+        equivocations[0]
+            .verify(&dkg_config, &bls_sig, &participants[0].public_key_sig)
+            .unwrap();
+        equivocations[0]
+            .verify(&dkg_config, &bls_sig, &participants[1].public_key_sig)
+            .unwrap_err();
+
+        match aggregator_a.receive_share(rng, &share_0_b) {
+            Err(DKGError::ConflictingShare(0)) => {}
+            _ => panic!("should have rejected the conflicting share"),
+        }
+        aggregator_a.equivocations[&0]
+            .verify(&dkg_config, &bls_sig, &participants[0].public_key_sig)
+            .unwrap();
+
+        // Recorded equivocators keep being aggregated.
+        aggregator_b
+            .receive_transcript(rng, &aggregator_a.transcript)
+            .unwrap();
+        assert!(aggregator_b.equivocations.contains_key(&0));
+
+        // Excluded equivocators are not.
+        let mut aggregator_c = new_node(&dealers[0]).aggregator;
+        aggregator_c.equivocation_policy = EquivocationPolicy::Exclude;
+        aggregator_c.receive_share(rng, &share_0_a).unwrap();
+        match aggregator_c.receive_transcript(rng, &aggregator_b.transcript) {
+            Err(DKGError::ExcludedEquivocator(0)) => {}
+            _ => panic!("should have excluded the equivocator"),
+        }
+        match aggregator_c.receive_share(rng, &share_0_a) {
+            Err(DKGError::ExcludedEquivocator(0)) => {}
+            _ => panic!("should have excluded the equivocator"),
+        }
+        aggregator_c.receive_share(rng, &share_1).unwrap();
+    }
+
+    #[test]
+    fn test_merge_transcript() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+
+        let shares = dealers
+            .iter()
+            .map(|d| new_node(d).share(rng).unwrap())
+            .collect::<Vec<_>>();
+        let mut children = vec![];
+        for ids in [vec![0, 1], vec![2], vec![0, 1, 2]].iter() {
+            let mut aggregator = new_node(&dealers[0]).aggregator;
+            for id in ids.iter() {
+                aggregator.receive_share(rng, &shares[*id]).unwrap();
+            }
+            children.push(aggregator.transcript);
+        }
+
+        let mut aggregator = new_node(&dealers[3]).aggregator;
+        let merges = children
+            .iter()
+            .chain(children.iter())
+            .map(|transcript| aggregator.merge_transcript(rng, transcript).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            merges,
+            vec![
+                TranscriptMerge::Replaced,
+                TranscriptMerge::Aggregated,
+                TranscriptMerge::Unchanged,
+                TranscriptMerge::Unchanged,
+                TranscriptMerge::Unchanged,
+                TranscriptMerge::Unchanged,
+            ]
+        );
+        assert_eq!(aggregator.transcript.contributions.len(), 3);
+        aggregator.output(rng, &aggregator.transcript).unwrap();
+
+        let mut other_aggregator = new_node(&dealers[3]).aggregator;
+        assert_eq!(
+            other_aggregator
+                .merge_transcript(rng, &children[1])
+                .unwrap(),
+            TranscriptMerge::Replaced
+        );
+        assert_eq!(
+            other_aggregator
+                .merge_transcript(rng, &children[2])
+                .unwrap(),
+            TranscriptMerge::Replaced
+        );
+        other_aggregator.receive_share(rng, &shares[3]).unwrap();
+        aggregator.receive_share(rng, &shares[3]).unwrap();
+        assert_eq!(
+            aggregator
+                .merge_transcript(rng, &other_aggregator.transcript)
+                .unwrap(),
+            TranscriptMerge::Unchanged
+        );
+        assert_eq!(
+            aggregator.transcript.pvss_share.y_i,
+            other_aggregator.transcript.pvss_share.y_i
+        );
+    }
+
+    #[test]
+    fn test_verification_cache() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+        let shares = dealers
+            .iter()
+            .map(|d| new_node(d).share(rng).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut child = new_node(&dealers[0]).aggregator;
+        child.receive_share(rng, &shares[0]).unwrap();
+        child.receive_share(rng, &shares[1]).unwrap();
+        let first_transcript = child.transcript.clone();
+        child.receive_share(rng, &shares[2]).unwrap();
+        let second_transcript = child.transcript.clone();
+
+        let mut aggregator = new_node(&dealers[3]).aggregator;
+        let cache =
+            |aggregator: &DKGAggregator<Bls12_381, PokScheme, SigScheme>,
+             transcript: &DKGTranscript<Bls12_381, PokScheme, SigScheme>| {
+                aggregator
+                    .verification_cache
+                    .unverified_contributions(transcript)
+                    .unwrap()
+            };
+        assert_eq!(cache(&aggregator, &first_transcript), None);
+        aggregator
+            .receive_transcript(rng, &first_transcript)
+            .unwrap();
+        assert_eq!(cache(&aggregator, &first_transcript), Some(vec![]));
+
+        // The second transcript is the first one plus a share that hasn't been seen yet.
+        assert_eq!(cache(&aggregator, &second_transcript), None);
+        aggregator.receive_share(rng, &shares[2]).unwrap();
+        assert_eq!(cache(&aggregator, &second_transcript), Some(vec![]));
+        aggregator
+            .receive_transcript(rng, &second_transcript)
+            .unwrap();
+
+        // Only the signature that differs from the verified one is checked.
+        let mut bad_signature = second_transcript.clone();
+        bad_signature
+            .contributions
+            .get_mut(&1)
+            .unwrap()
+            .signature_on_c_i = second_transcript.contributions[&0].signature_on_c_i;
+        assert_eq!(cache(&aggregator, &bad_signature), Some(vec![1]));
+        aggregator
+            .receive_transcript(rng, &bad_signature)
+            .unwrap_err();
+
+        // A share with a weight above one is added once, scaled by its weight.
+        let mut share_aggregator = new_node(&dealers[0]).aggregator;
+        share_aggregator.receive_share(rng, &shares[0]).unwrap();
+        let mut weighted = first_transcript.clone();
+        for _ in 0..2 {
+            weighted = weighted.aggregate(&share_aggregator.transcript).unwrap();
+        }
+        assert_eq!(weighted.contributions[&0].weight, 3);
+        share_aggregator.receive_share(rng, &shares[1]).unwrap();
+        assert_eq!(cache(&share_aggregator, &weighted), Some(vec![]));
+        share_aggregator.receive_transcript(rng, &weighted).unwrap();
+
+        // A PVSS share that isn't the sum of verified ones is fully verified.
+        let mut bad_pvss_share = second_transcript.clone();
+        bad_pvss_share.pvss_share.y_i.swap(0, 1);
+        assert_eq!(cache(&aggregator, &bad_pvss_share), None);
+        aggregator
+            .receive_transcript(rng, &bad_pvss_share)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_weight_bound() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+        let transcripts = dealers
+            .iter()
+            .map(|d| {
+                let mut node = new_node(d);
+                let share = node.share(rng).unwrap();
+                node.aggregator.receive_share(rng, &share).unwrap();
+                node.aggregator.transcript
+            })
+            .collect::<Vec<_>>();
+
+        // Valid transcripts where participant 0 contributed with the maximum weight.
+        let mut heaviest = transcripts[0].clone();
+        heaviest.pvss_share = heaviest.pvss_share.scale(MAX_CONTRIBUTION_WEIGHT);
+        heaviest.contributions.get_mut(&0).unwrap().weight = MAX_CONTRIBUTION_WEIGHT;
+        let first = heaviest.aggregate(&transcripts[1]).unwrap();
+        let second = heaviest.aggregate(&transcripts[2]).unwrap();
+
+        let mut aggregator = new_node(&dealers[3]).aggregator;
+        assert!(matches!(
+            aggregator.merge_transcript(rng, &first),
+            Ok(TranscriptMerge::Replaced)
+        ));
+        assert!(matches!(
+            aggregator.merge_transcript(rng, &second),
+            Err(DKGError::WeightTooLarge(0))
+        ));
+        assert_eq!(aggregator.transcript.total_weight(), first.total_weight());
+
+        let mut too_heavy = first.clone();
+        too_heavy.contributions.get_mut(&1).unwrap().weight = u64::MAX;
+        assert!(matches!(
+            aggregator.receive_transcript(rng, &too_heavy),
+            Err(DKGError::WeightTooLarge(1))
+        ));
+        let mut bytes = vec![];
+        too_heavy.serialize(&mut bytes).unwrap();
+        assert!(matches!(
+            DKGTranscript::<Bls12_381, PokScheme, SigScheme>::decode(
+                &bytes,
+                dkg_config.degree,
+                participants.len()
+            ),
+            Err(DKGError::WeightTooLarge(1))
+        ));
+    }
+
+    #[test]
+    fn test_receive_shares() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(5, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_node = |dealer: &Dealer<Bls12_381, SigScheme>| {
+            Node::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                dealer.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap()
+        };
+        let shares = dealers
+            .iter()
+            .map(|d| new_node(d).share(rng).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut aggregator = new_node(&dealers[0]).aggregator;
+        aggregator.receive_shares(rng, &shares).unwrap();
+        let mut other_aggregator = new_node(&dealers[0]).aggregator;
+        for share in shares.iter() {
+            other_aggregator.receive_share(rng, share).unwrap();
+        }
+        assert_eq!(
+            aggregator.transcript.pvss_share.y_i,
+            other_aggregator.transcript.pvss_share.y_i
+        );
+
+        // The batches follow the configured challenge mode and batch scalars.
+        let mut small_aggregator = new_node(&dealers[0]).aggregator;
+        small_aggregator.batch_scalars = BatchScalars::Small(128);
+        small_aggregator.challenge_mode = ChallengeMode::FiatShamir;
+        small_aggregator
+            .receive_shares(&mut PanicRng, &shares)
+            .unwrap();
+        assert_eq!(
+            small_aggregator.transcript.pvss_share.y_i,
+            other_aggregator.transcript.pvss_share.y_i
+        );
+
+        let mut bad_shares = shares.clone();
+        bad_shares[1].pvss_share.y_i.swap(0, 1);
+        bad_shares[3].signature_on_c_i = shares[0].signature_on_c_i;
+        bad_shares.push(shares[0].clone());
+        let mut aggregator = new_node(&dealers[0]).aggregator;
+        match aggregator.receive_shares(rng, &bad_shares) {
+            Err(DKGError::RejectedShares(participant_ids)) => {
+                assert_eq!(participant_ids, vec![0, 1, 3])
+            }
+            _ => panic!("should have rejected the bad shares"),
+        }
+        assert_eq!(
+            aggregator
+                .transcript
+                .contributions
+                .keys()
+                .copied()
+                .collect::<Vec<_>>(),
+            vec![0, 2, 4]
+        );
+        aggregator.output(rng, &aggregator.transcript).unwrap();
+    }
+
+    // Fails the test if any randomness is drawn from it.
+    struct PanicRng;
+
+    impl RngCore for PanicRng {
+        fn next_u32(&mut self) -> u32 {
+            panic!("should not use the rng")
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            panic!("should not use the rng")
+        }
+
+        fn fill_bytes(&mut self, _: &mut [u8]) {
+            panic!("should not use the rng")
+        }
+
+        fn try_fill_bytes(&mut self, _: &mut [u8]) -> Result<(), rand::Error> {
+            panic!("should not use the rng")
+        }
+    }
+
+    #[test]
+    fn test_fiat_shamir_challenges() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_aggregator = || {
+            let mut aggregator = DKGAggregator::new(
+                &mut thread_rng(),
+                dkg_config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .unwrap();
+            aggregator.challenge_mode = ChallengeMode::FiatShamir;
+            aggregator
+        };
+        let shares = dealers
+            .iter()
+            .map(|d| {
+                Node::new(
+                    rng,
+                    dkg_config.clone(),
+                    bls_pok.clone(),
+                    bls_sig.clone(),
+                    d.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap()
+                .share(rng)
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut aggregator = new_aggregator();
+        aggregator.receive_share(&mut PanicRng, &shares[0]).unwrap();
+        aggregator
+            .receive_shares(&mut PanicRng, &shares[1..])
+            .unwrap();
+        let transcript = aggregator.transcript.clone();
+
+        let mut other_aggregator = new_aggregator();
+        other_aggregator
+            .receive_transcript(&mut PanicRng, &transcript)
+            .unwrap();
+        other_aggregator.output(&mut PanicRng, &transcript).unwrap();
+
+        let mut bad_transcript = transcript.clone();
+        bad_transcript.pvss_share.y_i.swap(0, 1);
+        for _ in 0..2 {
+            match new_aggregator().transcript_verify(&mut PanicRng, &bad_transcript) {
+                Err(DKGError::InvalidPVSSShare(report)) => {
+                    assert_eq!(report.invalid_recipients, vec![0, 1])
+                }
+                _ => panic!("should have rejected the PVSS share"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_batch_scalars() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            bls_pok.clone(),
+            bls_sig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        aggregator.batch_scalars = BatchScalars::Small(128);
+        aggregator.verification_mode = VerificationMode::Blame;
+        let empty_aggregator = aggregator.clone();
+
+        let mut shares = dealers
+            .iter()
+            .map(|dealer| {
+                Node {
+                    aggregator: empty_aggregator.clone(),
+                    dealer: dealer.clone(),
+                }
+                .share(rng)
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        aggregator.receive_shares(rng, &shares).unwrap();
+        let transcript = aggregator.transcript.clone();
+        empty_aggregator
+            .clone()
+            .receive_transcript(rng, &transcript)
+            .unwrap();
+
+        let mut bad_contributions = transcript.clone();
+        bad_contributions
+            .contributions
+            .get_mut(&2)
+            .unwrap()
+            .signature_on_c_i = transcript.contributions[&0].signature_on_c_i;
+        match empty_aggregator
+            .clone()
+            .receive_transcript(rng, &bad_contributions)
+        {
+            Err(DKGError::InvalidContributions(signatures, poks)) => {
+                assert_eq!(signatures, vec![2]);
+                assert!(poks.is_empty());
+            }
+            _ => panic!("should have found the invalid contributions"),
+        }
+
+        let mut bad_pvss_share = transcript.clone();
+        bad_pvss_share.pvss_share.y_i.swap(1, 3);
+        match empty_aggregator
+            .clone()
+            .receive_transcript(rng, &bad_pvss_share)
+        {
+            Err(DKGError::InvalidPVSSShare(report)) => {
+                assert_eq!(report.invalid_recipients, vec![1, 3])
+            }
+            _ => panic!("should have rejected the PVSS share"),
+        }
+
+        shares[1].pvss_share.a_i.swap(0, 2);
+        match empty_aggregator.clone().receive_shares(rng, &shares) {
+            Err(DKGError::RejectedShares(participant_ids)) => assert_eq!(participant_ids, vec![1]),
+            _ => panic!("should have rejected the bad share"),
+        }
+    }
+
+    #[test]
+    fn test_session_binding() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(2, 1);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let new_aggregator = |config: &Config<Bls12_381>| {
+            DKGAggregator::new(
+                &mut thread_rng(),
+                config.clone(),
+                bls_pok.clone(),
+                bls_sig.clone(),
+                participants.clone().into_iter().enumerate().collect(),
+            )
+            .map_err(Box::new)
+        };
+
+        let mut node = Node {
+            aggregator: new_aggregator(&dkg_config).unwrap(),
+            dealer: dealers[0].clone(),
+        };
+        let share = node.share(rng).unwrap();
+        let mut aggregator = new_aggregator(&dkg_config).unwrap();
+        aggregator.receive_share(rng, &share).unwrap();
+
+        // Signatures from one session can't be replayed into another one.
+        let next_session_config = Config {
+            session_id: b"next session".to_vec(),
+            ..dkg_config.clone()
+        };
+        let mut next_session_aggregator = new_aggregator(&next_session_config).unwrap();
+        next_session_aggregator
+            .receive_share(rng, &share)
+            .unwrap_err();
+        next_session_aggregator
+            .receive_transcript(rng, &aggregator.transcript)
+            .unwrap_err();
+
+        let other_committee_config = Config {
+            committee_digest: committee_digest(&participants[..1]).unwrap(),
+            ..dkg_config
+        };
+        match new_aggregator(&other_committee_config) {
+            Err(error) => assert!(matches!(*error, DKGError::CommitteeDigestMismatch)),
+            _ => panic!("should have rejected the committee digest"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_participant_keys() {
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(3, 1);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let registration_error = |participants: &[Participant<Bls12_381, SigScheme>]| {
+            let config = Config {
+                committee_digest: committee_digest(participants).unwrap(),
+                ..dkg_config.clone()
+            };
+            DKGAggregator::new(
+                &mut thread_rng(),
+                config,
+                bls_pok.clone(),
+                bls_sig.clone(),
+                participants.iter().cloned().enumerate().collect(),
+            )
+            .err()
+        };
+        assert!(registration_error(&participants).is_none());
+
+        let mut unproven = participants.clone();
+        unproven[1].proof_of_possession = unproven[2].proof_of_possession;
+        assert!(matches!(
+            registration_error(&unproven),
+            Some(DKGError::InvalidProofOfPossession(1))
+        ));
+
+        let mut zero = participants.clone();
+        zero[1].public_key_sig = G2Affine::zero();
+        assert!(matches!(
+            registration_error(&zero),
+            Some(DKGError::InvalidPublicKey(1))
+        ));
+
+        let out_of_subgroup_key = out_of_subgroup_g2_point();
+        let mut out_of_subgroup = participants;
+        out_of_subgroup[1].public_key_sig = out_of_subgroup_key;
+        assert!(matches!(
+            registration_error(&out_of_subgroup),
+            Some(DKGError::InvalidPublicKey(1))
+        ));
+    }
+
+    #[test]
+    fn test_blame() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            bls_pok.clone(),
+            bls_sig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        for dealer in dealers.iter() {
+            let mut node = Node {
+                aggregator: aggregator.clone(),
+                dealer: dealer.clone(),
+            };
             let share = node.share(rng).unwrap();
-            for j in 0..NODES {
-                nodes[j]
-                    .receive_share_and_decrypt(rng, share.clone())
-                    .unwrap();
+            aggregator.receive_share(rng, &share).unwrap();
+        }
+        let transcript = aggregator.transcript.clone();
+
+        let mut bad_contributions = transcript.clone();
+        bad_contributions
+            .contributions
+            .get_mut(&1)
+            .unwrap()
+            .signature_on_c_i = transcript.contributions[&2].signature_on_c_i;
+        bad_contributions.contributions.get_mut(&3).unwrap().c_i_pok =
+            transcript.contributions[&0].c_i_pok.clone();
+        let mut bad_pvss_share = transcript.clone();
+        bad_pvss_share.pvss_share.u_i_2 = G2Projective::rand(rng).into_affine();
+
+        aggregator.verification_mode = VerificationMode::Batch;
+        aggregator.receive_transcript(rng, &transcript).unwrap();
+        match aggregator.receive_transcript(rng, &bad_contributions) {
+            Err(DKGError::SignatureError(_)) => {}
+            _ => panic!("should have failed the signature batch check"),
+        }
+
+        aggregator.verification_mode = VerificationMode::Blame;
+        aggregator.receive_transcript(rng, &transcript).unwrap();
+        match aggregator.receive_transcript(rng, &bad_contributions) {
+            Err(DKGError::InvalidContributions(signatures, poks)) => {
+                assert_eq!(signatures, vec![1]);
+                assert_eq!(poks, vec![3]);
+            }
+            _ => panic!("should have found the invalid contributions"),
+        }
+        match aggregator.receive_transcript(rng, &bad_pvss_share) {
+            Err(DKGError::InvalidPVSSShare(report)) => {
+                assert!(report.evaluations_correct);
+                assert!(!report.ratio_correct);
+                assert!(report.encryption_correct);
             }
+            _ => panic!("should have failed the PVSS check"),
+        }
+    }
+
+    #[test]
+    fn test_pvss_share_report() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(5, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut node = Node::new(
+            rng,
+            dkg_config,
+            bls_pok,
+            bls_sig,
+            dealers[0].clone(),
+            participants.into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        let share = node.share(rng).unwrap();
+        let aggregator = &node.aggregator;
+
+        let report = aggregator
+            .pvss_share_report(rng, share.c_i, &share.pvss_share)
+            .unwrap();
+        assert!(report.is_valid());
+        assert!(report.invalid_recipients.is_empty());
+
+        let mut bad_ciphertexts = share.pvss_share.clone();
+        bad_ciphertexts.y_i[1] = G2Projective::rand(rng).into_affine();
+        bad_ciphertexts.y_i[3] = bad_ciphertexts.y_i[4];
+        let report = aggregator
+            .pvss_share_report(rng, share.c_i, &bad_ciphertexts)
+            .unwrap();
+        assert!(report.evaluations_correct);
+        assert!(report.ratio_correct);
+        assert!(!report.encryption_correct);
+        assert_eq!(report.invalid_recipients, vec![1, 3]);
+        match aggregator.pvss_share_verify(rng, share.c_i, &bad_ciphertexts) {
+            Err(DKGError::InvalidPVSSShare(r)) => assert_eq!(r, report),
+            _ => panic!("should have failed the encryption check"),
+        }
+
+        let mut truncated = share.pvss_share.clone();
+        truncated.y_i.pop();
+        assert!(matches!(
+            aggregator.pvss_share_report(rng, share.c_i, &truncated),
+            Err(DKGError::WrongLength("y_i", 5, 4))
+        ));
+
+        let mut bad_evaluation = share.pvss_share;
+        bad_evaluation.a_i[2] = G1Projective::rand(rng).into_affine();
+        let report = aggregator
+            .pvss_share_report(rng, share.c_i, &bad_evaluation)
+            .unwrap();
+        assert!(!report.evaluations_correct);
+        assert!(report.ratio_correct);
+        assert_eq!(report.invalid_recipients, vec![2]);
+    }
+
+    #[test]
+    fn test_exact_verifier_agrees() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(5, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut nodes = dealers
+            .iter()
+            .map(|dealer| {
+                Node::new(
+                    rng,
+                    dkg_config.clone(),
+                    bls_pok.clone(),
+                    bls_sig.clone(),
+                    dealer.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let shares = nodes
+            .iter_mut()
+            .map(|node| node.share(rng).unwrap())
+            .collect::<Vec<_>>();
+        let mut aggregator = nodes[0].aggregator.clone();
+        for share in shares.iter().take(3) {
+            aggregator.receive_share(rng, share).unwrap();
+        }
+        let transcript = aggregator.transcript.clone();
+        let c = transcript
+            .contributions
+            .values()
+            .fold(G1Projective::zero(), |acc, contribution| {
+                acc + contribution.c_i.mul(Fr::from(contribution.weight))
+            })
+            .into_affine();
+
+        let share = &shares[1];
+        let mut cases = vec![
+            (share.c_i, share.pvss_share.clone()),
+            (c, transcript.pvss_share.clone()),
+            (shares[2].c_i, share.pvss_share.clone()),
+        ];
+        let mut swapped = share.pvss_share.clone();
+        swapped.y_i.swap(0, 4);
+        cases.push((share.c_i, swapped));
+        let mut bad_ciphertext = transcript.pvss_share.clone();
+        bad_ciphertext.y_i[2] = G2Projective::rand(rng).into_affine();
+        cases.push((c, bad_ciphertext));
+        let mut bad_evaluation = share.pvss_share.clone();
+        bad_evaluation.a_i[3] = G1Projective::rand(rng).into_affine();
+        cases.push((share.c_i, bad_evaluation));
+        let mut bad_coefficient = transcript.pvss_share.clone();
+        bad_coefficient.f_i[1] = G1Projective::rand(rng).into_affine();
+        cases.push((c, bad_coefficient));
+        let mut bad_ratio = share.pvss_share.clone();
+        bad_ratio.u_i_2 = G2Projective::rand(rng).into_affine();
+        cases.push((share.c_i, bad_ratio));
+
+        for (i, (c_i, pvss_share)) in cases.iter().enumerate() {
+            let fast = aggregator.pvss_share_report(rng, *c_i, pvss_share).unwrap();
+            let exact = aggregator
+                .pvss_share_report_exact(*c_i, pvss_share)
+                .unwrap();
+            assert_eq!(fast.is_valid(), exact.is_valid(), "case {}", i);
+            assert_eq!(fast.is_valid(), i < 2, "case {}", i);
+            assert_eq!(
+                fast.evaluations_correct,
+                exact.wrong_evaluations().is_empty(),
+                "case {}",
+                i
+            );
+            assert_eq!(fast.ratio_correct, exact.ratio_correct, "case {}", i);
+            assert_eq!(
+                fast.encryption_correct,
+                exact.invalid_recipients().is_empty(),
+                "case {}",
+                i
+            );
+            assert_eq!(
+                fast.invalid_recipients,
+                exact.invalid_recipients(),
+                "case {}",
+                i
+            );
+        }
+    }
+
+    #[test]
+    fn test_structural_validation() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut node = Node::new(
+            rng,
+            dkg_config,
+            bls_pok,
+            bls_sig,
+            dealers[0].clone(),
+            participants.into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        let share = node.share(rng).unwrap();
+        let mut aggregator = node.aggregator.clone();
+        aggregator.receive_share(rng, &share).unwrap();
+        let transcript = aggregator.transcript.clone();
+        aggregator.receive_transcript(rng, &transcript).unwrap();
+
+        let mut error = |modify: &dyn Fn(&mut DKGTranscript<_, _, _>)| {
+            let mut bad_transcript = transcript.clone();
+            modify(&mut bad_transcript);
+            aggregator
+                .receive_transcript(&mut thread_rng(), &bad_transcript)
+                .unwrap_err()
+        };
+        assert!(matches!(
+            error(&|t| t.degree = 3),
+            DKGError::TranscriptConfigMismatch(3, 2, 4, 4)
+        ));
+        assert!(matches!(
+            error(&|t| t.num_participants = 5),
+            DKGError::TranscriptConfigMismatch(2, 2, 5, 4)
+        ));
+        assert!(matches!(
+            error(&|t| t.contributions.clear()),
+            DKGError::EmptyTranscript
+        ));
+        assert!(matches!(
+            error(&|t| {
+                let contribution = t.contributions[&0].clone();
+                t.contributions.insert(4, contribution);
+            }),
+            DKGError::InvalidParticipantId(4)
+        ));
+        assert!(matches!(
+            error(&|t| t.contributions.get_mut(&0).unwrap().weight = 0),
+            DKGError::ZeroWeight(0)
+        ));
+        assert!(matches!(
+            error(&|t| t.contributions.get_mut(&0).unwrap().c_i = G1Affine::zero()),
+            DKGError::IdentityCommitment(0)
+        ));
+        assert!(matches!(
+            error(&|t| t.pvss_share.f_i.push(G1Affine::zero())),
+            DKGError::WrongLength("f_i", 2, 3)
+        ));
+        assert!(matches!(
+            error(&|t| {
+                t.pvss_share.a_i.pop();
+            }),
+            DKGError::WrongLength("a_i", 4, 3)
+        ));
+        assert!(matches!(
+            error(&|t| {
+                t.pvss_share.y_i.pop();
+            }),
+            DKGError::WrongLength("y_i", 4, 3)
+        ));
+        assert!(matches!(
+            error(&|t| t.pvss_share.u_i_2 = G2Affine::zero()),
+            DKGError::IdentityElement("u_i_2")
+        ));
+
+        let mut truncated = transcript.clone();
+        truncated.pvss_share.a_i.pop();
+        assert!(transcript.aggregate(&truncated).is_err());
+
+        let mut truncated_share = share;
+        truncated_share.pvss_share.y_i.pop();
+        let mut aggregator = node.aggregator;
+        match aggregator.receive_share(rng, &truncated_share) {
+            Err(DKGError::WrongLength("y_i", 4, 3)) => {}
+            _ => panic!("should have rejected the truncated share"),
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let rng = &mut thread_rng();
+        let (dkg_config, bls_pok, bls_sig, dealers) = setup_bls(4, 2);
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+        let mut node = Node::new(
+            rng,
+            dkg_config,
+            bls_pok,
+            bls_sig,
+            dealers[0].clone(),
+            participants.into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        let share = node.share(rng).unwrap();
+        let mut aggregator = node.aggregator.clone();
+        aggregator.receive_share(rng, &share).unwrap();
+        let transcript = aggregator.transcript.clone();
+
+        let mut share_bytes = vec![];
+        share.serialize(&mut share_bytes).unwrap();
+        let decoded_share = aggregator.decode_share(&share_bytes).unwrap();
+        assert_eq!(decoded_share.c_i, share.c_i);
+        assert_eq!(decoded_share.pvss_share.y_i, share.pvss_share.y_i);
+        let mut transcript_bytes = vec![];
+        transcript.serialize(&mut transcript_bytes).unwrap();
+        let decoded_transcript = aggregator.decode_transcript(&transcript_bytes).unwrap();
+        aggregator
+            .receive_transcript(rng, &decoded_transcript)
+            .unwrap();
+
+        match aggregator.decode_share(&share_bytes[..share_bytes.len() - 1]) {
+            Err(DKGError::DecodeError("signature_on_c_i", _)) => {}
+            _ => panic!("should have failed to decode the truncated share"),
+        }
+        let mut padded = transcript_bytes.clone();
+        padded.push(0);
+        match aggregator.decode_transcript(&padded) {
+            Err(DKGError::TrailingBytes(1)) => {}
+            _ => panic!("should have rejected the trailing bytes"),
+        }
+
+        // A huge length is rejected before reading any element.
+        let mut bad_length = share.clone();
+        bad_length.pvss_share.f_i.clear();
+        let mut bad_length_bytes = vec![];
+        bad_length.serialize(&mut bad_length_bytes).unwrap();
+        let f_i_offset = 8;
+        bad_length_bytes[f_i_offset..f_i_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        match aggregator.decode_share(&bad_length_bytes) {
+            Err(DKGError::WrongLength("f_i", 2, _)) => {}
+            _ => panic!("should have rejected the length"),
+        }
+
+        // A point outside of the prime order subgroup is rejected.
+        let out_of_subgroup_point = out_of_subgroup_g2_point();
+        let mut bad_point = share;
+        bad_point.pvss_share.y_i[2] = out_of_subgroup_point;
+        let mut bad_point_bytes = vec![];
+        bad_point.serialize(&mut bad_point_bytes).unwrap();
+        match aggregator.decode_share(&bad_point_bytes) {
+            Err(DKGError::DecodeError("y_i", _)) => {}
+            _ => panic!("should have rejected the point"),
         }
     }
 
@@ -416,7 +1678,7 @@ mod test {
     }
 
     fn test_2_nodes_and_aggregator_with_signature_scheme<
-        SPOK: BatchVerifiableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = G1Affine, Secret = Fr>,
         SSIG: BatchVerifiableSignatureScheme<PublicKey = G2Affine, Secret = Fr>,
     >(
         srs: SRS<Bls12_381>,
@@ -428,20 +1690,13 @@ mod test {
         let rng = &mut thread_rng();
 
         let u_1 = G2Projective::rand(rng).into_affine();
-        let dkg_config = Config {
-            srs: srs.clone(),
-            u_1,
-            degree: 2,
-        };
 
         let mut dealers = vec![];
         for i in 0..NODES {
             let dealer_keypair_sig = ssig.generate_keypair(rng).unwrap();
             let participant = Participant {
-                pairing_type: PhantomData,
-                id: i,
-                public_key_sig: dealer_keypair_sig.1,
                 state: ParticipantState::Dealer,
+                ..Participant::register(rng, &ssig, i, &dealer_keypair_sig.0).unwrap()
             };
             let dealer = Dealer {
                 private_key_sig: dealer_keypair_sig.0,
@@ -456,33 +1711,41 @@ mod test {
             .iter()
             .map(|d| d.participant.clone())
             .collect::<Vec<_>>();
-        let num_participants = participants.len();
 
-        let mut aggregator = DKGAggregator {
-            config: dkg_config.clone(),
-            scheme_pok: spok.clone(),
-            scheme_sig: ssig.clone(),
-            participants: participants.clone().into_iter().enumerate().collect(),
-            transcript: DKGTranscript::empty(dkg_config.degree, num_participants),
+        let dkg_config = Config {
+            srs: srs.clone(),
+            u_1,
+            degree: 2,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(&participants).unwrap(),
         };
 
+        let mut aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            spok.clone(),
+            ssig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+
         let mut nodes = vec![];
-        for i in 0..NODES {
-            let degree = dkg_config.degree;
+        for dealer in dealers.iter() {
             let node = Node {
-                aggregator: DKGAggregator {
-                    config: dkg_config.clone(),
-                    scheme_pok: spok.clone(),
-                    scheme_sig: ssig.clone(),
-                    participants: participants.clone().into_iter().enumerate().collect(),
-                    transcript: DKGTranscript::empty(degree, num_participants),
-                },
-                dealer: dealers[i].clone(),
+                aggregator: DKGAggregator::new(
+                    rng,
+                    dkg_config.clone(),
+                    spok.clone(),
+                    ssig.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap(),
+                dealer: dealer.clone(),
             };
             nodes.push(node);
         }
-        // Make participant 0 have weight 2.
-        // Should ignore participant 1, since we modify its share to be bad.
+        // Participant 0 sends its share twice, which is ignored, and then a different one, which is
+        // rejected. Participant 1's share is rejected, since we modify it to be bad.
         for i in 0..NODES {
             let node = &mut nodes[i];
             let mut share = node.share(rng).unwrap();
@@ -491,51 +1754,149 @@ mod test {
                     share.c_i = G1Projective::rand(rng).into_affine();
                 }
 
-                nodes[j]
-                    .receive_share_and_decrypt(rng, share.clone())
-                    .unwrap();
+                let result = nodes[j].receive_share_and_decrypt(rng, share.clone());
+                if i == 1 {
+                    assert!(matches!(result, Err(DKGError::InvalidPVSSShare(_))));
+                } else {
+                    assert!(result.unwrap());
+                }
                 if i == 0 {
-                    nodes[j]
+                    assert!(!nodes[j]
                         .receive_share_and_decrypt(rng, share.clone())
-                        .unwrap();
+                        .unwrap());
+                    let conflicting_share = nodes[i].share(rng).unwrap();
+                    assert!(matches!(
+                        nodes[j].receive_share_and_decrypt(rng, conflicting_share),
+                        Err(DKGError::ConflictingShare(0))
+                    ));
                 }
             }
             if i != 1 {
                 aggregator.receive_share(rng, &share.clone()).unwrap();
                 if i == 0 {
-                    aggregator.receive_share(rng, &share.clone()).unwrap();
+                    match aggregator.receive_share(rng, &share.clone()) {
+                        Err(DKGError::DuplicateShare(0)) => {}
+                        _ => panic!("should have rejected the repeated share"),
+                    }
+                    let mut conflicting_share = nodes[i].share(rng).unwrap();
+                    match aggregator.receive_share(rng, &conflicting_share) {
+                        Err(DKGError::ConflictingShare(0)) => {}
+                        _ => panic!("should have rejected the conflicting share"),
+                    }
+                    conflicting_share.c_i = G1Projective::rand(rng).into_affine();
+                    assert!(aggregator.receive_share(rng, &conflicting_share).is_err());
                 }
             } else {
                 aggregator.receive_share(rng, &share.clone()).unwrap_err();
             }
         }
 
-        let transcript = aggregator.transcript;
+        let transcript = aggregator.transcript.clone();
+        let output = aggregator.output(rng, &transcript).unwrap();
+        assert_eq!(output.threshold(), dkg_config.degree + 1);
         for i in 0..NODES {
-            let degree = dkg_config.degree;
             let mut node = Node {
-                aggregator: DKGAggregator {
-                    config: dkg_config.clone(),
-                    scheme_pok: spok.clone(),
-                    scheme_sig: ssig.clone(),
-                    participants: participants.clone().into_iter().enumerate().collect(),
-                    transcript: DKGTranscript::empty(degree, num_participants),
-                },
+                aggregator: DKGAggregator::new(
+                    rng,
+                    dkg_config.clone(),
+                    spok.clone(),
+                    ssig.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap(),
                 dealer: dealers[i].clone(),
             };
-            node.receive_transcript_and_decrypt(rng, transcript.clone())
-                .unwrap();
+            assert!(node
+                .receive_transcript_and_decrypt(rng, transcript.clone())
+                .unwrap());
+            assert!(!node
+                .receive_transcript_and_decrypt(rng, transcript.clone())
+                .unwrap());
+            // The transcript has the same contributors and weight as the shares received by
+            // nodes[i], so nothing changes.
+            assert!(!nodes[i]
+                .receive_transcript_and_decrypt(rng, transcript.clone())
+                .unwrap());
             assert_eq!(
                 node.dealer.accumulated_secret,
                 nodes[i].dealer.accumulated_secret
             );
-            if i == 0 {
-                assert_eq!(transcript.contributions[&i].weight, 2);
-            } else if i == 1 {
-                assert!(transcript.contributions.get(&i).is_none());
+            assert_eq!(
+                Bls12_381::pairing(output.participant_public_key(i).unwrap(), srs.h_g2),
+                Bls12_381::pairing(srs.g_g1, node.dealer.accumulated_secret)
+            );
+            if i == 1 {
+                assert!(!transcript.contributions.contains_key(&i));
             } else {
                 assert_eq!(transcript.contributions[&i].weight, 1);
             }
         }
+
+        // Half-aggregating the POKs shrinks the transcript, which still decodes and verifies.
+        let aggregated = transcript.half_aggregate_poks(&dkg_config, &spok).unwrap();
+        let mut transcript_bytes = vec![];
+        transcript.serialize(&mut transcript_bytes).unwrap();
+        let mut aggregated_bytes = vec![];
+        aggregated.serialize(&mut aggregated_bytes).unwrap();
+        assert!(aggregated_bytes.len() < transcript_bytes.len());
+        let decoded = aggregator.decode_transcript(&aggregated_bytes).unwrap();
+        let aggregated_output = aggregator.output(rng, &decoded).unwrap();
+        assert_eq!(
+            aggregated_output.participant_public_key(0).unwrap(),
+            output.participant_public_key(0).unwrap()
+        );
+        aggregator.receive_transcript(rng, &aggregated).unwrap();
+        // It can't become the local transcript, which must still accept shares.
+        assert!(matches!(
+            aggregator.merge_transcript(rng, &aggregated),
+            Err(DKGError::AggregatedPoks)
+        ));
+        assert!(matches!(
+            nodes[0].receive_transcript_and_decrypt(rng, aggregated.clone()),
+            Err(DKGError::AggregatedPoks)
+        ));
+        assert!(nodes[0].aggregator.transcript.aggregated_pok.is_none());
+        let mut late_aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            spok.clone(),
+            ssig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        assert!(matches!(
+            late_aggregator.merge_transcript(rng, &aggregated),
+            Err(DKGError::AggregatedPoks)
+        ));
+        let share = nodes[0].share(rng).unwrap();
+        late_aggregator.receive_share(rng, &share).unwrap();
+        assert!(matches!(
+            aggregated.half_aggregate_poks(&dkg_config, &spok),
+            Err(DKGError::AggregatedPoks)
+        ));
+        assert!(matches!(
+            aggregated.aggregate(&transcript),
+            Err(DKGError::AggregatedPoks)
+        ));
+
+        let mut partial = transcript.clone();
+        partial.contributions.remove(&0);
+        let mut wrong_response = aggregated.clone();
+        wrong_response.aggregated_pok = partial
+            .half_aggregate_poks(&dkg_config, &spok)
+            .unwrap()
+            .aggregated_pok;
+        assert!(matches!(
+            aggregator.transcript_verify(rng, &wrong_response),
+            Err(DKGError::InvalidAggregatedPok)
+        ));
+        let mut mixed = aggregated.clone();
+        mixed
+            .contributions
+            .insert(0, transcript.contributions[&0].clone());
+        assert!(matches!(
+            aggregator.transcript_verify(rng, &mixed),
+            Err(DKGError::InconsistentPok(0))
+        ));
     }
 }
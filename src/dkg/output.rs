@@ -0,0 +1,88 @@
+use crate::{
+    dkg::{errors::DKGError, share::DKGTranscript},
+    signature::scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
+};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use blake2s_simd::Params;
+
+const PERSONALIZATION: &[u8] = b"DKGOUTPT";
+
+/// The public result of a DKG, as read from a verified transcript.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct DKGOutput<E: PairingEngine> {
+    pub degree: usize,
+    pub num_participants: usize,
+    /// `g_g1^{f(0)}`, the weighted sum of the `c_i` of all contributions.
+    pub group_public_key: E::G1Affine,
+    /// `u_1^{f(0)}`, the aggregated `u_i_2`.
+    pub group_public_key_g2: E::G2Affine,
+    /// `a_i = g_g1^{f(omega^i)}`, the verification key of participant `i`.
+    pub participant_public_keys: Vec<E::G1Affine>,
+    pub digest: Vec<u8>,
+}
+
+impl<E: PairingEngine> DKGOutput<E> {
+    /// Assumes that the transcript has already been verified, e.g. by
+    /// `DKGAggregator::transcript_verify`.
+    pub fn from_verified_transcript<
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    >(
+        transcript: &DKGTranscript<E, SPOK, SSIG>,
+    ) -> Result<Self, DKGError<E>> {
+        let group_public_key = transcript
+            .contributions
+            .values()
+            .fold(E::G1Projective::zero(), |acc, contribution| {
+                acc + contribution
+                    .c_i
+                    .mul(<E::Fr as From<u64>>::from(contribution.weight))
+            })
+            .into_affine();
+
+        let mut output = Self {
+            degree: transcript.degree,
+            num_participants: transcript.num_participants,
+            group_public_key,
+            group_public_key_g2: transcript.pvss_share.u_i_2,
+            participant_public_keys: transcript.pvss_share.a_i.clone(),
+            digest: vec![],
+        };
+        output.digest = output.compute_digest()?;
+
+        Ok(output)
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.degree + 1
+    }
+
+    pub fn participant_public_key(
+        &self,
+        participant_id: usize,
+    ) -> Result<E::G1Affine, DKGError<E>> {
+        self.participant_public_keys
+            .get(participant_id)
+            .cloned()
+            .ok_or(DKGError::<E>::InvalidParticipantId(participant_id))
+    }
+
+    fn compute_digest(&self) -> Result<Vec<u8>, DKGError<E>> {
+        let mut bytes = vec![];
+        self.degree.serialize(&mut bytes)?;
+        self.num_participants.serialize(&mut bytes)?;
+        self.group_public_key.serialize(&mut bytes)?;
+        self.group_public_key_g2.serialize(&mut bytes)?;
+        self.participant_public_keys.serialize(&mut bytes)?;
+
+        let hash = Params::new()
+            .hash_length(32)
+            .personal(PERSONALIZATION)
+            .to_state()
+            .update(&bytes)
+            .finalize();
+        Ok(hash.as_bytes().to_vec())
+    }
+}
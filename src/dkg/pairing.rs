@@ -0,0 +1,105 @@
+use ark_ec::PairingEngine;
+use ark_ff::One;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Checks that the product of the pairings is one. With the `parallel` feature, the Miller loops
+/// of chunks of pairs run on separate threads, and their product goes through a single final
+/// exponentiation, which gives the same result as `E::product_of_pairings`.
+pub fn product_of_pairings_is_one<E: PairingEngine>(
+    pairs: &[(E::G1Prepared, E::G2Prepared)],
+) -> bool {
+    E::final_exponentiation(&miller_loop::<E>(pairs)) == Some(E::Fqk::one())
+}
+
+// The product of the Miller loops of the pairs, equal to `E::miller_loop` of all of them.
+fn miller_loop<E: PairingEngine>(pairs: &[(E::G1Prepared, E::G2Prepared)]) -> E::Fqk {
+    #[cfg(feature = "parallel")]
+    let miller_loop = {
+        let chunk_size = (pairs.len() / rayon::current_num_threads()).max(1);
+        pairs
+            .par_chunks(chunk_size)
+            .map(|chunk| E::miller_loop(chunk.iter()))
+            .reduce(E::Fqk::one, |a, b| a * b)
+    };
+
+    #[cfg(not(feature = "parallel"))]
+    let miller_loop = E::miller_loop(pairs.iter());
+
+    miller_loop
+}
+
+#[cfg(test)]
+mod test {
+    use super::{miller_loop, product_of_pairings_is_one};
+    use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+    use ark_ec::{PairingEngine, ProjectiveCurve};
+    use ark_ff::{One, PrimeField, UniformRand};
+    use rand::thread_rng;
+    use std::ops::Neg;
+
+    #[test]
+    fn test_product_of_pairings_is_one() {
+        let rng = &mut thread_rng();
+        let mut pairs = vec![];
+        for _ in 0..17 {
+            let a = Fr::rand(rng);
+            let g = G1Projective::rand(rng);
+            let h = G2Projective::rand(rng);
+            pairs.push((g.mul(a.into_repr()), h));
+            pairs.push((g.neg(), h.mul(a.into_repr())));
+        }
+        let mut pairs = pairs
+            .into_iter()
+            .map(|(g, h)| (g.into_affine().into(), h.into_affine().into()))
+            .collect::<Vec<(
+                <Bls12_381 as PairingEngine>::G1Prepared,
+                <Bls12_381 as PairingEngine>::G2Prepared,
+            )>>();
+        assert!(Bls12_381::product_of_pairings(pairs.iter()).is_one());
+        assert!(product_of_pairings_is_one::<Bls12_381>(&pairs));
+        assert!(product_of_pairings_is_one::<Bls12_381>(&[]));
+
+        pairs.swap_remove(5);
+        assert!(!Bls12_381::product_of_pairings(pairs.iter()).is_one());
+        assert!(!product_of_pairings_is_one::<Bls12_381>(&pairs));
+    }
+
+    #[test]
+    fn test_miller_loop_is_bit_identical() {
+        let rng = &mut thread_rng();
+        let pairs = (0..33)
+            .map(|_| {
+                (
+                    G1Projective::rand(rng).into_affine().into(),
+                    G2Projective::rand(rng).into_affine().into(),
+                )
+            })
+            .collect::<Vec<(
+                <Bls12_381 as PairingEngine>::G1Prepared,
+                <Bls12_381 as PairingEngine>::G2Prepared,
+            )>>();
+        let check = || {
+            for count in [0, 1, 2, 33] {
+                let pairs = &pairs[..count];
+                let f = miller_loop::<Bls12_381>(pairs);
+                assert_eq!(f, Bls12_381::miller_loop(pairs.iter()));
+                assert_eq!(
+                    Bls12_381::final_exponentiation(&f).unwrap(),
+                    Bls12_381::product_of_pairings(pairs.iter())
+                );
+            }
+        };
+
+        // Several threads, so that the pairs are split into chunks whatever the machine.
+        #[cfg(feature = "parallel")]
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap()
+            .install(check);
+
+        #[cfg(not(feature = "parallel"))]
+        check();
+    }
+}
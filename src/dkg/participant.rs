@@ -1,5 +1,20 @@
-use crate::signature::scheme::BatchVerifiableSignatureScheme;
+use crate::{
+    dkg::errors::DKGError,
+    signature::{
+        scheme::BatchVerifiableSignatureScheme,
+        utils::group::is_in_correct_subgroup_assuming_on_curve,
+    },
+};
 use ark_ec::PairingEngine;
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
+use ark_std::collections::BTreeMap;
+use blake2s_simd::Params;
+use rand::Rng;
+use std::marker::PhantomData;
+
+const PERSONALIZATION: &[u8] = b"DKGCMTEE";
+const PROOF_OF_POSSESSION_DOMAIN: &[u8] = b"DKG participant proof of possession";
 
 #[derive(Clone)]
 pub enum ParticipantState {
@@ -18,5 +33,127 @@ pub struct Participant<
     pub pairing_type: std::marker::PhantomData<E>,
     pub id: usize,
     pub public_key_sig: SSIG::PublicKey,
+    /// Signature by `public_key_sig` on `proof_of_possession_message`.
+    pub proof_of_possession: SSIG::Signature,
     pub state: ParticipantState,
 }
+
+impl<
+        E: PairingEngine,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    > Participant<E, SSIG>
+{
+    pub fn register<R: Rng>(
+        rng: &mut R,
+        scheme_sig: &SSIG,
+        id: usize,
+        private_key_sig: &SSIG::Secret,
+    ) -> Result<Self, DKGError<E>> {
+        let (private_key_sig, public_key_sig) = scheme_sig.from_sk(private_key_sig)?;
+        let proof_of_possession = scheme_sig.sign(
+            rng,
+            &private_key_sig,
+            &proof_of_possession_message(id, &public_key_sig)?,
+        )?;
+        Ok(Self {
+            pairing_type: PhantomData,
+            id,
+            public_key_sig,
+            proof_of_possession,
+            state: ParticipantState::Initial,
+        })
+    }
+
+    /// The key is used both for signatures and for encrypting the PVSS evaluations, so it has to
+    /// be a non-zero element of the prime order subgroup.
+    pub fn check_public_key(&self) -> Result<(), DKGError<E>> {
+        if self.public_key_sig.is_zero()
+            || !is_in_correct_subgroup_assuming_on_curve(&self.public_key_sig)
+        {
+            return Err(DKGError::InvalidPublicKey(self.id));
+        }
+        Ok(())
+    }
+
+    pub fn verify_registration(&self, scheme_sig: &SSIG) -> Result<(), DKGError<E>> {
+        self.check_public_key()?;
+        scheme_sig
+            .verify(
+                &self.public_key_sig,
+                &proof_of_possession_message(self.id, &self.public_key_sig)?,
+                &self.proof_of_possession,
+            )
+            .map_err(|_| DKGError::InvalidProofOfPossession(self.id))
+    }
+}
+
+pub fn proof_of_possession_message<E: PairingEngine>(
+    id: usize,
+    public_key_sig: &E::G2Affine,
+) -> Result<Vec<u8>, DKGError<E>> {
+    let mut message = PROOF_OF_POSSESSION_DOMAIN.to_vec();
+    id.serialize(&mut message)?;
+    public_key_sig.serialize(&mut message)?;
+    Ok(message)
+}
+
+/// Verifies the registrations of all the participants, batching the proofs of possession. If the
+/// batch fails, the proofs are verified one by one to find an invalid one.
+pub fn verify_registrations<
+    E: PairingEngine,
+    SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    R: Rng,
+>(
+    rng: &mut R,
+    scheme_sig: &SSIG,
+    participants: &BTreeMap<usize, Participant<E, SSIG>>,
+) -> Result<(), DKGError<E>> {
+    let mut public_keys = vec![];
+    let mut messages = vec![];
+    let mut signatures = vec![];
+    for participant in participants.values() {
+        participant.check_public_key()?;
+        public_keys.push(&participant.public_key_sig);
+        messages.push(proof_of_possession_message(
+            participant.id,
+            &participant.public_key_sig,
+        )?);
+        signatures.push(&participant.proof_of_possession);
+    }
+    let batch_result = scheme_sig.batch_verify(
+        rng,
+        &public_keys,
+        &messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>(),
+        &signatures,
+    );
+    if let Err(e) = batch_result {
+        for participant in participants.values() {
+            participant.verify_registration(scheme_sig)?;
+        }
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Digest of the ids and signature public keys of the participants, in the given order.
+pub fn committee_digest<
+    'a,
+    E: PairingEngine,
+    SSIG: 'a + BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+>(
+    participants: impl IntoIterator<Item = &'a Participant<E, SSIG>>,
+) -> Result<Vec<u8>, DKGError<E>> {
+    let mut bytes = vec![];
+    for participant in participants {
+        participant.id.serialize(&mut bytes)?;
+        participant.public_key_sig.serialize(&mut bytes)?;
+    }
+
+    let hash = Params::new()
+        .hash_length(32)
+        .personal(PERSONALIZATION)
+        .to_state()
+        .update(&bytes)
+        .finalize();
+    Ok(hash.as_bytes().to_vec())
+}
@@ -0,0 +1,30 @@
+use crate::{
+    dkg::{config::Config, participant::Participant},
+    signature::scheme::BatchVerifiableSignatureScheme,
+};
+use ark_ec::PairingEngine;
+use ark_std::collections::BTreeMap;
+
+/// The G2 points paired in every verification, prepared once for the whole DKG since the config
+/// and the participants don't change.
+#[derive(Clone)]
+pub struct PreparedG2<E: PairingEngine> {
+    pub u_1: E::G2Prepared,
+    /// Indexed by participant id.
+    pub public_keys_sig: Vec<E::G2Prepared>,
+}
+
+impl<E: PairingEngine> PreparedG2<E> {
+    pub fn new<SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>>(
+        config: &Config<E>,
+        participants: &BTreeMap<usize, Participant<E, SSIG>>,
+    ) -> Self {
+        Self {
+            u_1: config.u_1.into(),
+            public_keys_sig: participants
+                .values()
+                .map(|participant| participant.public_key_sig.into())
+                .collect(),
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use ark_ec::PairingEngine;
+use crate::dkg::{
+    decode::{read, read_vec},
+    errors::DKGError,
+};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::Zero;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 
@@ -13,15 +17,63 @@ pub struct PVSSShare<E: PairingEngine> {
 impl<E: PairingEngine> PVSSShare<E> {
     pub fn empty(degree: usize, num_participants: usize) -> Self {
         PVSSShare {
-            f_i: vec![E::G1Affine::zero(); degree + 1],
+            f_i: vec![E::G1Affine::zero(); degree],
             u_i_2: E::G2Affine::zero(),
             a_i: vec![E::G1Affine::zero(); num_participants],
             y_i: vec![E::G2Affine::zero(); num_participants],
         }
     }
 
-    pub fn aggregate(&self, other: &Self) -> Self {
+    /// Checks the lengths against the config. `f_i` holds the commitments to the coefficients of
+    /// degree 1 to `degree`, the coefficient of degree 0 being committed to by `c_i`.
+    pub fn validate(&self, degree: usize, num_participants: usize) -> Result<(), DKGError<E>> {
+        let lengths = [
+            ("f_i", degree, self.f_i.len()),
+            ("a_i", num_participants, self.a_i.len()),
+            ("y_i", num_participants, self.y_i.len()),
+        ];
+        for (name, expected, actual) in lengths.iter() {
+            if expected != actual {
+                return Err(DKGError::WrongLength(name, *expected, *actual));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a share written by `serialize`, rejecting lengths that don't match the config.
+    pub fn decode<R: Read>(
+        reader: &mut R,
+        degree: usize,
+        num_participants: usize,
+    ) -> Result<Self, DKGError<E>> {
+        Ok(Self {
+            f_i: read_vec(reader, "f_i", degree)?,
+            u_i_2: read(reader, "u_i_2")?,
+            a_i: read_vec(reader, "a_i", num_participants)?,
+            y_i: read_vec(reader, "y_i", num_participants)?,
+        })
+    }
+
+    /// Returns the share aggregated `weight` times with itself.
+    pub fn scale(&self, weight: u64) -> Self {
+        let weight = <E::Fr as From<u64>>::from(weight);
+        let f_i = self.f_i.iter().map(|f| f.mul(weight)).collect::<Vec<_>>();
+        let a_i = self.a_i.iter().map(|a| a.mul(weight)).collect::<Vec<_>>();
+        let y_i = self.y_i.iter().map(|y| y.mul(weight)).collect::<Vec<_>>();
         Self {
+            f_i: E::G1Projective::batch_normalization_into_affine(&f_i),
+            u_i_2: self.u_i_2.mul(weight).into_affine(),
+            a_i: E::G1Projective::batch_normalization_into_affine(&a_i),
+            y_i: E::G2Projective::batch_normalization_into_affine(&y_i),
+        }
+    }
+
+    pub fn aggregate(&self, other: &Self) -> Result<Self, DKGError<E>> {
+        other.validate(self.f_i.len(), self.a_i.len())?;
+        if self.y_i.len() != self.a_i.len() {
+            return Err(DKGError::WrongLength("y_i", self.a_i.len(), self.y_i.len()));
+        }
+        Ok(Self {
             f_i: self
                 .f_i
                 .iter()
@@ -41,8 +93,67 @@ impl<E: PairingEngine> PVSSShare<E> {
                 .zip(other.y_i.iter())
                 .map(|(y1, y2)| *y1 + *y2)
                 .collect::<Vec<_>>(),
-        }
+        })
+    }
+}
+
+/// The outcome of each check of `DKGAggregator::pvss_share_verify`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PvssVerificationReport<E: PairingEngine> {
+    /// The commitment the share was checked against.
+    pub c_i: E::G1Affine,
+    /// The `a_i` are evaluations of the polynomial committed to by `c_i` and `f_i`.
+    pub evaluations_correct: bool,
+    /// `c_i` and `u_i_2` have the same discrete logarithm.
+    pub ratio_correct: bool,
+    /// Each `y_i` encrypts the evaluation in `a_i` to the key of participant `i`.
+    pub encryption_correct: bool,
+    /// Participants whose `y_i` doesn't match `a_i` and their key.
+    pub invalid_recipients: Vec<usize>,
+}
+
+impl<E: PairingEngine> PvssVerificationReport<E> {
+    pub fn is_valid(&self) -> bool {
+        self.evaluations_correct && self.ratio_correct && self.encryption_correct
+    }
+}
+
+/// The outcome of each check of `DKGAggregator::pvss_share_report_exact`, per participant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PvssExactReport<E: PairingEngine> {
+    /// The commitment the share was checked against.
+    pub c_i: E::G1Affine,
+    /// `c_i` and `u_i_2` have the same discrete logarithm.
+    pub ratio_correct: bool,
+    /// Whether each `a_i` is the evaluation of the polynomial committed to by `c_i` and `f_i`.
+    pub evaluations_correct: Vec<bool>,
+    /// Whether each `y_i` encrypts the evaluation in `a_i` to the key of participant `i`.
+    pub encryptions_correct: Vec<bool>,
+}
+
+impl<E: PairingEngine> PvssExactReport<E> {
+    pub fn is_valid(&self) -> bool {
+        self.ratio_correct
+            && self.evaluations_correct.iter().all(|c| *c)
+            && self.encryptions_correct.iter().all(|c| *c)
     }
+
+    pub fn wrong_evaluations(&self) -> Vec<usize> {
+        failures(&self.evaluations_correct)
+    }
+
+    pub fn invalid_recipients(&self) -> Vec<usize> {
+        failures(&self.encryptions_correct)
+    }
+}
+
+fn failures(checks: &[bool]) -> Vec<usize> {
+    checks
+        .iter()
+        .enumerate()
+        .filter(|(_, correct)| !**correct)
+        .map(|(i, _)| i)
+        .collect()
 }
 
 pub struct PVSSShareSecrets<E: PairingEngine> {
@@ -1,16 +1,27 @@
 use crate::{
-    dkg::{errors::DKGError, pvss::PVSSShare},
-    signature::scheme::BatchVerifiableSignatureScheme,
+    dkg::{
+        config::Config,
+        decode::{ensure_consumed, read, read_len},
+        equivocation::EquivocationProof,
+        errors::DKGError,
+        pvss::PVSSShare,
+    },
+    signature::scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme},
 };
 use ark_ec::PairingEngine;
+use ark_ff::Zero;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 use ark_std::collections::BTreeMap;
 use std::io::Cursor;
 
+/// The largest weight of a contribution. Honest weights count how many times a share was
+/// aggregated, so they stay far below it, and sums of weights can't overflow.
+pub const MAX_CONTRIBUTION_WEIGHT: u64 = 1 << 32;
+
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
 pub struct DKGShare<
     E: PairingEngine,
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
 > {
     pub participant_id: usize,
@@ -23,48 +34,285 @@ pub struct DKGShare<
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
 pub struct DKGTranscriptParticipant<
     E: PairingEngine,
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
 > {
     pub c_i: E::G1Affine,
     pub weight: u64,
-    pub c_i_pok: SPOK::Signature,
+    pub c_i_pok: ContributionPok<SPOK>,
     pub signature_on_c_i: SSIG::Signature,
 }
 
+/// The POK of a contribution to a transcript. Once the POKs of a transcript are half-aggregated,
+/// contributions only keep the commitments of their POKs, and the transcript holds the aggregated
+/// response.
+#[derive(Clone)]
+pub enum ContributionPok<SPOK: HalfAggregatableSignatureScheme> {
+    Signature(SPOK::Signature),
+    Aggregated(SPOK::Commitment),
+}
+
 #[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
 pub struct DKGTranscript<
     E: PairingEngine,
-    SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+    SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
     SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
 > {
     pub degree: usize,
     pub num_participants: usize,
     pub contributions: BTreeMap<usize, DKGTranscriptParticipant<E, SPOK, SSIG>>,
     pub pvss_share: PVSSShare<E>,
+    /// The half-aggregated response of the POKs, when the contributions only keep commitments.
+    pub aggregated_pok: Option<SPOK::Response>,
 }
 
-pub fn message_from_c_i<E: PairingEngine>(c_i: E::G1Affine) -> Result<Vec<u8>, DKGError<E>> {
+pub fn message_from_c_i<E: PairingEngine>(
+    config: &Config<E>,
+    c_i: E::G1Affine,
+) -> Result<Vec<u8>, DKGError<E>> {
     let mut message_writer = Cursor::new(vec![]);
+    config.session_id.serialize(&mut message_writer)?;
+    config.committee_digest.serialize(&mut message_writer)?;
     c_i.serialize(&mut message_writer)?;
     Ok(message_writer.get_ref().to_vec())
 }
 
+impl<SPOK: HalfAggregatableSignatureScheme> CanonicalSerialize for ContributionPok<SPOK> {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        match self {
+            ContributionPok::Signature(signature) => {
+                0u8.serialize(&mut writer)?;
+                signature.serialize(&mut writer)
+            }
+            ContributionPok::Aggregated(commitment) => {
+                1u8.serialize(&mut writer)?;
+                commitment.serialize(&mut writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        1 + match self {
+            ContributionPok::Signature(signature) => signature.serialized_size(),
+            ContributionPok::Aggregated(commitment) => commitment.serialized_size(),
+        }
+    }
+}
+
+impl<SPOK: HalfAggregatableSignatureScheme> CanonicalDeserialize for ContributionPok<SPOK> {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            0 => Ok(ContributionPok::Signature(SPOK::Signature::deserialize(
+                &mut reader,
+            )?)),
+            1 => Ok(ContributionPok::Aggregated(SPOK::Commitment::deserialize(
+                &mut reader,
+            )?)),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+}
+
+impl<
+        E: PairingEngine,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    > DKGShare<E, SPOK, SSIG>
+{
+    /// Decodes a share received from the network, checking every point and length.
+    pub fn decode(
+        bytes: &[u8],
+        degree: usize,
+        num_participants: usize,
+    ) -> Result<Self, DKGError<E>> {
+        let mut reader = bytes;
+        let participant_id = read(&mut reader, "participant_id")?;
+        if participant_id >= num_participants {
+            return Err(DKGError::InvalidParticipantId(participant_id));
+        }
+        let share = Self {
+            participant_id,
+            pvss_share: PVSSShare::decode(&mut reader, degree, num_participants)?,
+            c_i: read(&mut reader, "c_i")?,
+            c_i_pok: read(&mut reader, "c_i_pok")?,
+            signature_on_c_i: read(&mut reader, "signature_on_c_i")?,
+        };
+        ensure_consumed(reader)?;
+        Ok(share)
+    }
+}
+
 impl<
         E: PairingEngine,
-        SPOK: BatchVerifiableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
+        SPOK: HalfAggregatableSignatureScheme<PublicKey = E::G1Affine, Secret = E::Fr>,
         SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
     > DKGTranscript<E, SPOK, SSIG>
 {
+    /// Decodes a transcript received from the network, checking every point and length.
+    pub fn decode(
+        bytes: &[u8],
+        degree: usize,
+        num_participants: usize,
+    ) -> Result<Self, DKGError<E>> {
+        let mut reader = bytes;
+        let transcript_degree = read(&mut reader, "degree")?;
+        let transcript_num_participants = read(&mut reader, "num_participants")?;
+        if transcript_degree != degree || transcript_num_participants != num_participants {
+            return Err(DKGError::TranscriptConfigMismatch(
+                transcript_degree,
+                degree,
+                transcript_num_participants,
+                num_participants,
+            ));
+        }
+
+        let num_contributions = read_len(&mut reader, "contributions")?;
+        if num_contributions > num_participants {
+            return Err(DKGError::TooManyElements(
+                "contributions",
+                num_participants,
+                num_contributions,
+            ));
+        }
+        let mut contributions = BTreeMap::new();
+        for _ in 0..num_contributions {
+            let participant_id = read(&mut reader, "participant_id")?;
+            if participant_id >= num_participants || contributions.contains_key(&participant_id) {
+                return Err(DKGError::InvalidParticipantId(participant_id));
+            }
+            let contribution = DKGTranscriptParticipant {
+                c_i: read(&mut reader, "c_i")?,
+                weight: read(&mut reader, "weight")?,
+                c_i_pok: read(&mut reader, "c_i_pok")?,
+                signature_on_c_i: read(&mut reader, "signature_on_c_i")?,
+            };
+            if contribution.weight > MAX_CONTRIBUTION_WEIGHT {
+                return Err(DKGError::WeightTooLarge(participant_id));
+            }
+            contributions.insert(participant_id, contribution);
+        }
+
+        let transcript = Self {
+            degree,
+            num_participants,
+            contributions,
+            pvss_share: PVSSShare::decode(&mut reader, degree, num_participants)?,
+            aggregated_pok: read(&mut reader, "aggregated_pok")?,
+        };
+        ensure_consumed(reader)?;
+        Ok(transcript)
+    }
+
     pub fn empty(degree: usize, num_participants: usize) -> Self {
         Self {
             degree,
             num_participants,
             contributions: BTreeMap::new(),
             pvss_share: PVSSShare::empty(degree, num_participants),
+            aggregated_pok: None,
         }
     }
 
+    /// Returns the transcript with its POKs half-aggregated, which for Schnorr POKs saves a scalar
+    /// per contribution. The result can be verified, but not aggregated with other transcripts.
+    pub fn half_aggregate_poks(
+        &self,
+        config: &Config<E>,
+        scheme_pok: &SPOK,
+    ) -> Result<Self, DKGError<E>> {
+        let mut public_keys = vec![];
+        let mut messages = vec![];
+        let mut signatures = vec![];
+        for contribution in self.contributions.values() {
+            match &contribution.c_i_pok {
+                ContributionPok::Signature(signature) => signatures.push(signature),
+                ContributionPok::Aggregated(_) => return Err(DKGError::AggregatedPoks),
+            }
+            public_keys.push(&contribution.c_i);
+            messages.push(message_from_c_i(config, contribution.c_i)?);
+        }
+        let response = scheme_pok.half_aggregate(
+            &public_keys,
+            &messages.iter().map(|v| v.as_slice()).collect::<Vec<_>>(),
+            &signatures,
+        )?;
+
+        let mut transcript = self.clone();
+        for contribution in transcript.contributions.values_mut() {
+            if let ContributionPok::Signature(signature) = &contribution.c_i_pok {
+                contribution.c_i_pok = ContributionPok::Aggregated(SPOK::commitment(signature));
+            }
+        }
+        transcript.aggregated_pok = Some(response);
+        Ok(transcript)
+    }
+
+    pub fn total_weight(&self) -> u128 {
+        self.contributions
+            .values()
+            .map(|c| u128::from(c.weight))
+            .sum()
+    }
+
+    /// Returns proofs for the participants that contributed different commitments to the two
+    /// transcripts. Only meaningful when both transcripts have been verified.
+    pub fn equivocations(&self, other: &Self) -> Vec<EquivocationProof<E, SSIG>> {
+        self.contributions
+            .iter()
+            .filter_map(|(participant_id, a)| {
+                let b = other.contributions.get(participant_id)?;
+                if a.c_i == b.c_i {
+                    return None;
+                }
+                Some(EquivocationProof {
+                    participant_id: *participant_id,
+                    c_i_1: a.c_i,
+                    signature_on_c_i_1: a.signature_on_c_i.clone(),
+                    c_i_2: b.c_i,
+                    signature_on_c_i_2: b.signature_on_c_i.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Checks that the transcript is well formed for the config, before any cryptographic check.
+    pub fn validate(&self, degree: usize, num_participants: usize) -> Result<(), DKGError<E>> {
+        if self.degree != degree || self.num_participants != num_participants {
+            return Err(DKGError::TranscriptConfigMismatch(
+                self.degree,
+                degree,
+                self.num_participants,
+                num_participants,
+            ));
+        }
+        if self.contributions.is_empty() {
+            return Err(DKGError::EmptyTranscript);
+        }
+        for (participant_id, contribution) in self.contributions.iter() {
+            if *participant_id >= num_participants {
+                return Err(DKGError::InvalidParticipantId(*participant_id));
+            }
+            if contribution.weight == 0 {
+                return Err(DKGError::ZeroWeight(*participant_id));
+            }
+            if contribution.weight > MAX_CONTRIBUTION_WEIGHT {
+                return Err(DKGError::WeightTooLarge(*participant_id));
+            }
+            if contribution.c_i.is_zero() {
+                return Err(DKGError::IdentityCommitment(*participant_id));
+            }
+            let aggregated = matches!(contribution.c_i_pok, ContributionPok::Aggregated(_));
+            if aggregated != self.aggregated_pok.is_some() {
+                return Err(DKGError::InconsistentPok(*participant_id));
+            }
+        }
+        self.pvss_share.validate(degree, num_participants)?;
+        if self.pvss_share.u_i_2.is_zero() {
+            return Err(DKGError::IdentityElement("u_i_2"));
+        }
+        Ok(())
+    }
+
     pub fn aggregate(&self, other: &Self) -> Result<Self, DKGError<E>> {
         if self.degree != other.degree || self.num_participants != other.num_participants {
             return Err(DKGError::TranscriptDifferentConfig(
@@ -74,16 +322,24 @@ impl<
                 other.num_participants,
             ));
         }
+        if self.aggregated_pok.is_some() || other.aggregated_pok.is_some() {
+            return Err(DKGError::AggregatedPoks);
+        }
         let contributions = (0..self.num_participants)
             .map(
                 |i| match (self.contributions.get(&i), other.contributions.get(&i)) {
                     (Some(a), Some(b)) => {
                         if a.c_i != b.c_i {
-                            return Err(DKGError::TranscriptDifferentCommitments);
+                            return Err(DKGError::TranscriptDifferentCommitments(i));
                         }
+                        let weight = a
+                            .weight
+                            .checked_add(b.weight)
+                            .filter(|weight| *weight <= MAX_CONTRIBUTION_WEIGHT)
+                            .ok_or(DKGError::WeightTooLarge(i))?;
                         let transcript_participant = DKGTranscriptParticipant {
                             c_i: a.c_i,
-                            weight: a.weight + b.weight,
+                            weight,
                             c_i_pok: a.c_i_pok.clone(),
                             signature_on_c_i: a.signature_on_c_i.clone(),
                         };
@@ -102,7 +358,8 @@ impl<
             degree: self.degree,
             num_participants: self.num_participants,
             contributions: contributions.into_iter().collect(),
-            pvss_share: self.pvss_share.aggregate(&other.pvss_share),
+            pvss_share: self.pvss_share.aggregate(&other.pvss_share)?,
+            aggregated_pok: None,
         };
         Ok(aggregated)
     }
@@ -3,3 +3,4 @@ extern crate ark_std;
 
 pub mod dkg;
 pub mod signature;
+pub mod vuf;
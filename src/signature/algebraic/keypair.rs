@@ -4,7 +4,10 @@ use super::{
     srs::SRS,
     PERSONALIZATION,
 };
-use crate::signature::utils::{errors::SignatureError, hash::hash_to_group};
+use crate::signature::utils::{
+    errors::SignatureError,
+    hash_to_curve::{HashToCurve, TryAndIncrement},
+};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{PrimeField, UniformRand};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
@@ -17,16 +20,16 @@ pub struct PrivateKey<E: PairingEngine> {
 }
 
 #[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct Keypair<E: PairingEngine> {
-    pub srs: SRS<E>,
+pub struct Keypair<E: PairingEngine, H: HashToCurve<E::G1Affine> = TryAndIncrement> {
+    pub srs: SRS<E, H>,
     pub alpha: E::Fr,
     pub beta: E::Fr,
     pub private: PrivateKey<E>,
-    pub public: PublicKey<E>,
+    pub public: PublicKey<E, H>,
 }
 
-impl<E: PairingEngine> Keypair<E> {
-    pub fn generate_keypair<R: Rng>(rng: &mut R, srs: SRS<E>) -> Result<Self, SignatureError> {
+impl<E: PairingEngine, H: HashToCurve<E::G1Affine>> Keypair<E, H> {
+    pub fn generate_keypair<R: Rng>(rng: &mut R, srs: SRS<E, H>) -> Result<Self, SignatureError> {
         let a = E::Fr::rand(rng);
         let a_g2 = srs.g_1_g2.mul(a.clone());
         let private_key = PrivateKey {
@@ -59,7 +62,7 @@ impl<E: PairingEngine> Keypair<E> {
     }
 
     pub fn sign(&self, message: &[u8]) -> Result<Signature<E>, SignatureError> {
-        let hashed_message = hash_to_group::<E::G1Affine>(PERSONALIZATION, message)?;
+        let hashed_message = H::hash_to_curve(PERSONALIZATION, message)?;
         let signature_proof = self.prove_signature(hashed_message)?;
 
         let signature = Signature { signature_proof };
@@ -67,7 +70,7 @@ impl<E: PairingEngine> Keypair<E> {
         Ok(signature)
     }
 
-    pub fn prove_key(&self) -> Result<ProvenPublicKey<E>, SignatureError> {
+    pub fn prove_key(&self) -> Result<ProvenPublicKey<E, H>, SignatureError> {
         let pi_1_g2 = self.srs.g_1_g2.mul(self.alpha.neg()) + &self.srs.g_2_g2.mul(self.beta.neg());
         let pi_2_g2 = self.srs.g_3_g2.mul(self.alpha.neg())
             + &self.srs.g_4_g2.mul(self.beta.neg())
@@ -10,7 +10,7 @@ mod test {
     use ark_bls12_381::Bls12_381;
 
     use super::{keypair::Keypair, public_key::ProvenPublicKey, signature::Signature, srs::SRS};
-    use crate::signature::utils::tests::check_serialization;
+    use crate::signature::utils::{hash_to_curve::Sswu, tests::check_serialization};
 
     use rand::thread_rng;
 
@@ -30,6 +30,31 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_simple_sig_sswu() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<Bls12_381, Sswu>::setup(rng).unwrap();
+        let keypair = Keypair::generate_keypair(rng, srs).unwrap();
+        let message = b"hello";
+
+        let proven_public_key = keypair.prove_key().unwrap();
+        proven_public_key.verify().unwrap();
+
+        let signature = keypair.sign(&message[..]).unwrap();
+        let derived = signature
+            .verify_and_derive(proven_public_key.clone(), &message[..])
+            .unwrap();
+        assert_eq!(
+            signature
+                .derive(proven_public_key.clone(), &message[..])
+                .unwrap(),
+            derived
+        );
+        signature
+            .verify(proven_public_key, &b"goodbye"[..])
+            .unwrap_err();
+    }
+
     #[test]
     #[should_panic]
     fn test_simple_sig_wrong_pk() {
@@ -208,6 +233,10 @@ mod test {
 
         let signature = keypair.sign(&message[..]).unwrap();
         proven_public_key.verify_probabilistically(rng).unwrap();
+        proven_public_key.verify_deterministically().unwrap();
+        let mut wrong_proof = proven_public_key.clone();
+        wrong_proof.key_proof.pi_2_g2 = wrong_proof.key_proof.pi_1_g2;
+        wrong_proof.verify_deterministically().unwrap_err();
         signature
             .verify_and_derive(proven_public_key, &message[..])
             .unwrap();
@@ -1,20 +1,26 @@
 use super::srs::SRS;
-use crate::signature::utils::errors::{SignatureError, VerifyProofEquation};
+use crate::signature::utils::{
+    errors::{SignatureError, VerifyProofEquation},
+    hash::hash_to_field,
+    hash_to_curve::{HashToCurve, TryAndIncrement},
+};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{One, PrimeField, UniformRand, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 use rand::Rng;
 use std::ops::Neg;
 
+const CHALLENGE_PERSONALIZATION: &[u8] = b"ALGEBCHL";
+
 #[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct PublicKey<E: PairingEngine> {
-    pub srs: SRS<E>,
+pub struct PublicKey<E: PairingEngine, H: HashToCurve<E::G1Affine> = TryAndIncrement> {
+    pub srs: SRS<E, H>,
     pub pk: E::G1Affine,
 }
 
 #[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct ProvenPublicKey<E: PairingEngine> {
-    pub public_key: PublicKey<E>,
+pub struct ProvenPublicKey<E: PairingEngine, H: HashToCurve<E::G1Affine> = TryAndIncrement> {
+    pub public_key: PublicKey<E, H>,
     pub key_proof: KeyProof<E>,
 }
 
@@ -26,8 +32,8 @@ pub struct KeyProof<E: PairingEngine> {
     pub pi_3_g1: E::G1Affine,
 }
 
-impl<E: PairingEngine> ProvenPublicKey<E> {
-    pub fn aggregate(public_keys: &[Self], srs: SRS<E>) -> Result<Self, SignatureError> {
+impl<E: PairingEngine, H: HashToCurve<E::G1Affine>> ProvenPublicKey<E, H> {
+    pub fn aggregate(public_keys: &[Self], srs: SRS<E, H>) -> Result<Self, SignatureError> {
         let zero_proven_public_key = Self {
             public_key: PublicKey {
                 srs,
@@ -112,7 +118,18 @@ impl<E: PairingEngine> ProvenPublicKey<E> {
     }
 
     pub fn verify_probabilistically<R: Rng>(&self, rng: &mut R) -> Result<(), SignatureError> {
-        let r = E::Fr::rand(rng);
+        self.verify_with_challenge(E::Fr::rand(rng))
+    }
+
+    /// Like `verify_probabilistically`, with the scalar combining the equations derived from a
+    /// hash of the proven public key instead of an rng.
+    pub fn verify_deterministically(&self) -> Result<(), SignatureError> {
+        let mut statement = vec![];
+        self.serialize(&mut statement)?;
+        self.verify_with_challenge(hash_to_field(CHALLENGE_PERSONALIZATION, &statement)?)
+    }
+
+    fn verify_with_challenge(&self, r: E::Fr) -> Result<(), SignatureError> {
         let eq = vec![
             (
                 self.public_key
@@ -1,7 +1,7 @@
 use super::{public_key::ProvenPublicKey, PERSONALIZATION};
 use crate::signature::utils::{
     errors::{SignatureError, VerifyProofEquation},
-    hash::hash_to_group,
+    hash_to_curve::HashToCurve,
 };
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{One, PrimeField, UniformRand, Zero};
@@ -32,32 +32,32 @@ impl<E: PairingEngine> Default for Signature<E> {
 }
 
 impl<E: PairingEngine> Signature<E> {
-    pub fn verify_and_derive(
+    pub fn verify_and_derive<H: HashToCurve<E::G1Affine>>(
         &self,
-        public_key: ProvenPublicKey<E>,
+        public_key: ProvenPublicKey<E, H>,
         message: &[u8],
     ) -> Result<E::Fqk, SignatureError> {
-        let hashed_message = hash_to_group::<E::G1Affine>(PERSONALIZATION, message)?;
+        let hashed_message = H::hash_to_curve(PERSONALIZATION, message)?;
         self.verify_proof(public_key.clone(), hashed_message)?;
 
         self.derive(public_key, message)
     }
 
-    pub fn verify(
+    pub fn verify<H: HashToCurve<E::G1Affine>>(
         &self,
-        public_key: ProvenPublicKey<E>,
+        public_key: ProvenPublicKey<E, H>,
         message: &[u8],
     ) -> Result<(), SignatureError> {
-        let hashed_message = hash_to_group::<E::G1Affine>(PERSONALIZATION, message)?;
+        let hashed_message = H::hash_to_curve(PERSONALIZATION, message)?;
         self.verify_proof(public_key.clone(), hashed_message)
     }
 
-    pub fn derive(
+    pub fn derive<H: HashToCurve<E::G1Affine>>(
         &self,
-        public_key: ProvenPublicKey<E>,
+        public_key: ProvenPublicKey<E, H>,
         message: &[u8],
     ) -> Result<E::Fqk, SignatureError> {
-        let hashed_message = hash_to_group::<E::G1Affine>(PERSONALIZATION, message)?;
+        let hashed_message = H::hash_to_curve(PERSONALIZATION, message)?;
         let sig_elements = vec![
             (
                 hashed_message.into_affine().into(),
@@ -77,9 +77,9 @@ impl<E: PairingEngine> Signature<E> {
         Ok(sig)
     }
 
-    fn verify_proof(
+    fn verify_proof<H: HashToCurve<E::G1Affine>>(
         &self,
-        public_key: ProvenPublicKey<E>,
+        public_key: ProvenPublicKey<E, H>,
         hashed_message: E::G1Projective,
     ) -> Result<(), SignatureError> {
         let eq2 = vec![
@@ -105,13 +105,13 @@ impl<E: PairingEngine> Signature<E> {
         Ok(())
     }
 
-    pub fn verify_all_probabilistically<R: Rng>(
+    pub fn verify_all_probabilistically<R: Rng, H: HashToCurve<E::G1Affine>>(
         &self,
         rng: &mut R,
-        public_key: ProvenPublicKey<E>,
+        public_key: ProvenPublicKey<E, H>,
         message: &[u8],
     ) -> Result<(), SignatureError> {
-        let hashed_message = hash_to_group::<E::G1Affine>(PERSONALIZATION, message)?;
+        let hashed_message = H::hash_to_curve(PERSONALIZATION, message)?;
         let r = E::Fr::rand(rng);
         let r2 = E::Fr::rand(rng);
         let eq = vec![
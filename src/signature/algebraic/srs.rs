@@ -1,20 +1,27 @@
-use crate::signature::utils::errors::SignatureError;
+use crate::signature::utils::{
+    errors::SignatureError,
+    hash_to_curve::{HashToCurve, TryAndIncrement},
+};
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::UniformRand;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 use rand::Rng;
+use std::marker::PhantomData;
 
 #[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
-pub struct SRS<E: PairingEngine> {
+pub struct SRS<E: PairingEngine, H: HashToCurve<E::G1Affine> = TryAndIncrement> {
     pub g_1_g2: E::G2Affine,
     pub h_g1: E::G1Affine,
 
     pub g_2_g2: E::G2Affine,
     pub g_3_g2: E::G2Affine,
     pub g_4_g2: E::G2Affine,
+
+    /// Selects how messages are hashed to G1.
+    pub hash_to_curve: PhantomData<H>,
 }
 
-impl<E: PairingEngine> SRS<E> {
+impl<E: PairingEngine, H: HashToCurve<E::G1Affine>> SRS<E, H> {
     pub fn setup<R: Rng>(rng: &mut R) -> Result<Self, SignatureError> {
         let srs = Self {
             g_1_g2: E::G2Affine::prime_subgroup_generator(),
@@ -23,6 +30,8 @@ impl<E: PairingEngine> SRS<E> {
             g_2_g2: E::G2Projective::rand(rng).into_affine(),
             g_3_g2: E::G2Projective::rand(rng).into_affine(),
             g_4_g2: E::G2Projective::rand(rng).into_affine(),
+
+            hash_to_curve: PhantomData,
         };
         Ok(srs)
     }
@@ -0,0 +1,516 @@
+use crate::signature::{
+    bls::{srs::SRS, BLSSignature, BLSSignatureScheme},
+    scheme::{
+        AggregatableSignatureScheme, BatchVerifiableSignatureScheme,
+        HalfAggregatableSignatureScheme, SignatureScheme,
+    },
+    utils::{
+        batch::{batch_challenge, powers, BatchScalars},
+        errors::SignatureError,
+        hash_to_curve::HashToCurve,
+    },
+};
+use ark_ec::AffineCurve;
+use ark_serialize::CanonicalSerialize;
+use rand::Rng;
+use std::{collections::BTreeSet, marker::PhantomData};
+
+/// The ways of the IRTF BLS signature draft to make aggregate signatures safe against rogue key
+/// attacks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CiphersuiteScheme {
+    /// Aggregate signatures must be on distinct messages.
+    Basic,
+    /// Signers sign their public key followed by the message.
+    MessageAugmentation,
+    /// Public keys come with proofs of possession of their secret keys, which must be verified
+    /// before the keys are aggregated.
+    ProofOfPossession,
+}
+
+impl CiphersuiteScheme {
+    fn tag(&self) -> &'static [u8] {
+        match self {
+            CiphersuiteScheme::Basic => b"NUL",
+            CiphersuiteScheme::MessageAugmentation => b"AUG",
+            CiphersuiteScheme::ProofOfPossession => b"POP",
+        }
+    }
+}
+
+/// A BLS ciphersuite of the IRTF BLS signature draft. The domain separation tags default to those
+/// of the draft, e.g. `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_`.
+///
+/// Only the basic scheme with messages hashed with `Sswu` is interoperable with other
+/// implementations. Message augmentation and proofs of possession sign the public key in ark's
+/// serialization rather than the Zcash encoding of the draft, so their signatures and proofs are
+/// not accepted elsewhere, nor theirs here.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BLSCiphersuite<B: BLSSignatureScheme> {
+    pub bls: BLSSignature<B>,
+    pub scheme: CiphersuiteScheme,
+    /// The domain separation tag of signatures.
+    pub dst: Vec<u8>,
+    /// The domain separation tag of proofs of possession.
+    pub pop_dst: Vec<u8>,
+}
+
+impl<B: BLSSignatureScheme> BLSCiphersuite<B> {
+    pub fn new(srs: SRS<B>, scheme: CiphersuiteScheme) -> Self {
+        let suite_id = B::Hasher::suite_id();
+        Self {
+            bls: BLSSignature { srs },
+            scheme,
+            dst: [b"BLS_SIG_", suite_id, scheme.tag(), b"_"].concat(),
+            pop_dst: [b"BLS_POP_", suite_id, b"POP_"].concat(),
+        }
+    }
+
+    /// Proves possession of the secret key of `sk`, by signing its public key.
+    pub fn pop_prove(
+        &self,
+        sk: &<B::PublicKeyGroup as AffineCurve>::ScalarField,
+    ) -> Result<B::SignatureGroup, SignatureError> {
+        let (_, public_key) = self.bls.from_sk(sk)?;
+        let mut public_key_bytes = vec![];
+        public_key.serialize(&mut public_key_bytes)?;
+        self.bls.core_sign(&self.pop_dst, sk, &public_key_bytes)
+    }
+
+    pub fn pop_verify(
+        &self,
+        public_key: &B::PublicKeyGroup,
+        proof: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        let mut public_key_bytes = vec![];
+        public_key.serialize(&mut public_key_bytes)?;
+        self.bls
+            .core_verify(&self.pop_dst, public_key, &public_key_bytes, proof)
+    }
+
+    /// Verifies a signature aggregated from signatures of each public key on its message, with a
+    /// single product of pairings.
+    pub fn aggregate_verify(
+        &self,
+        public_keys: &[&B::PublicKeyGroup],
+        messages: &[&[u8]],
+        signature: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        if self.scheme == CiphersuiteScheme::Basic
+            && messages.iter().collect::<BTreeSet<_>>().len() != messages.len()
+        {
+            return Err(SignatureError::DuplicateMessages);
+        }
+        let messages = self.augment_all(public_keys, messages)?;
+        let messages = messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+        self.bls
+            .core_aggregate_verify(&self.dst, public_keys, &messages, signature)
+    }
+
+    /// Verifies a signature aggregated from signatures of all the public keys on the same message.
+    /// Only allowed under proof of possession, for public keys whose proofs were verified.
+    pub fn fast_aggregate_verify(
+        &self,
+        public_keys: &[&B::PublicKeyGroup],
+        message: &[u8],
+        signature: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::BLSVerify);
+        }
+        let aggregated_public_key = self.aggregate_public_keys(public_keys)?;
+        self.verify(&aggregated_public_key, message, signature)
+    }
+
+    // The message that is actually signed: prefixed with the public key under message
+    // augmentation.
+    fn augment(
+        &self,
+        public_key: &B::PublicKeyGroup,
+        message: &[u8],
+    ) -> Result<Vec<u8>, SignatureError> {
+        let mut augmented = vec![];
+        if self.scheme == CiphersuiteScheme::MessageAugmentation {
+            public_key.serialize(&mut augmented)?;
+        }
+        augmented.extend_from_slice(message);
+        Ok(augmented)
+    }
+
+    // Checks the lengths before augmenting, as zipping would drop the extra messages.
+    fn augment_all(
+        &self,
+        public_keys: &[&B::PublicKeyGroup],
+        messages: &[&[u8]],
+    ) -> Result<Vec<Vec<u8>>, SignatureError> {
+        if public_keys.len() != messages.len() {
+            return Err(SignatureError::AggregateVerification(
+                public_keys.len(),
+                messages.len(),
+            ));
+        }
+        public_keys
+            .iter()
+            .zip(messages.iter())
+            .map(|(public_key, message)| self.augment(public_key, message))
+            .collect()
+    }
+}
+
+impl<B: BLSSignatureScheme> SignatureScheme for BLSCiphersuite<B> {
+    type SRS = SRS<B>;
+    type Secret = <B::PublicKeyGroup as AffineCurve>::ScalarField;
+    type PublicKey = B::PublicKeyGroup;
+    type Signature = B::SignatureGroup;
+
+    /// Uses the basic scheme, which is safe without any further checks.
+    fn from_srs(srs: Self::SRS) -> Result<Self, SignatureError> {
+        Ok(Self::new(srs, CiphersuiteScheme::Basic))
+    }
+
+    fn generate_keypair<R: Rng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(Self::Secret, Self::PublicKey), SignatureError> {
+        self.bls.generate_keypair(rng)
+    }
+
+    fn from_sk(
+        &self,
+        sk: &Self::Secret,
+    ) -> Result<(Self::Secret, Self::PublicKey), SignatureError> {
+        self.bls.from_sk(sk)
+    }
+
+    fn sign<R: Rng>(
+        &self,
+        _: &mut R,
+        sk: &Self::Secret,
+        message: &[u8],
+    ) -> Result<Self::Signature, SignatureError> {
+        let (_, public_key) = self.bls.from_sk(sk)?;
+        let message = self.augment(&public_key, message)?;
+        self.bls.core_sign(&self.dst, sk, &message)
+    }
+
+    fn verify(
+        &self,
+        public_key: &Self::PublicKey,
+        message: &[u8],
+        signature: &Self::Signature,
+    ) -> Result<(), SignatureError> {
+        let message = self.augment(public_key, message)?;
+        self.bls
+            .core_verify(&self.dst, public_key, &message, signature)
+    }
+}
+
+impl<B: BLSSignatureScheme> AggregatableSignatureScheme for BLSCiphersuite<B> {
+    /// Only allowed under proof of possession, for public keys whose proofs were verified.
+    fn aggregate_public_keys(
+        &self,
+        public_keys: &[&Self::PublicKey],
+    ) -> Result<Self::PublicKey, SignatureError> {
+        if self.scheme != CiphersuiteScheme::ProofOfPossession {
+            return Err(SignatureError::PublicKeyAggregation);
+        }
+        self.bls.aggregate_public_keys(public_keys)
+    }
+
+    fn aggregate_signatures(
+        &self,
+        signatures: &[&Self::Signature],
+    ) -> Result<Self::Signature, SignatureError> {
+        self.bls.aggregate_signatures(signatures)
+    }
+}
+
+impl<B: BLSSignatureScheme> BatchVerifiableSignatureScheme for BLSCiphersuite<B> {
+    fn batch_verify_with_scalars<R: Rng>(
+        &self,
+        rng: &mut R,
+        scalars: BatchScalars,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let messages = self.augment_all(public_keys, messages)?;
+        let messages = messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+        let pairs = self
+            .bls
+            .batch_pairs(&self.dst, public_keys, &messages, signatures)?;
+        let weights = scalars.sample(rng, pairs.len());
+        if !B::batch_product_of_pairings_is_one_weighted(&weights, pairs) {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+
+    fn batch_verify_deterministic(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let messages = self.augment_all(public_keys, messages)?;
+        let messages = messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+        let pairs = self
+            .bls
+            .batch_pairs(&self.dst, public_keys, &messages, signatures)?;
+        let alpha =
+            batch_challenge::<_, Self, _>(&self.bls.srs, public_keys, &messages, signatures)?;
+        let weights = powers(alpha, pairs.len());
+        if !B::batch_product_of_pairings_is_one_weighted(&weights, pairs) {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+}
+
+impl<B: BLSSignatureScheme> HalfAggregatableSignatureScheme for BLSCiphersuite<B> {
+    type Commitment = PhantomData<B::SignatureGroup>;
+    type Response = B::SignatureGroup;
+
+    fn commitment(_: &Self::Signature) -> Self::Commitment {
+        PhantomData
+    }
+
+    fn half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<Self::Response, SignatureError> {
+        self.bls.half_aggregate(public_keys, messages, signatures)
+    }
+
+    fn verify_half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        commitments: &[&Self::Commitment],
+        response: &Self::Response,
+    ) -> Result<(), SignatureError> {
+        if public_keys.len() != commitments.len() {
+            return Err(SignatureError::BatchVerification(
+                public_keys.len(),
+                messages.len(),
+                commitments.len(),
+            ));
+        }
+        self.aggregate_verify(public_keys, messages, response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BLSCiphersuite, CiphersuiteScheme};
+    use crate::signature::{
+        bls::{srs::SRS, BLSSignatureG1, BLSSignatureG2, BLSSignatureScheme},
+        scheme::{AggregatableSignatureScheme, BatchVerifiableSignatureScheme, SignatureScheme},
+        utils::{
+            batch::BatchScalars, errors::SignatureError,
+            group::is_in_correct_subgroup_assuming_on_curve, hash_to_curve::Sswu,
+        },
+    };
+    use ark_bls12_381::{Bls12_381, Fq, G1Affine, G2Affine};
+    use ark_ff::Zero;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_default_dsts() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<BLSSignatureG2<Bls12_381, Sswu>>::setup(rng).unwrap();
+        let pop = BLSCiphersuite::new(srs, CiphersuiteScheme::ProofOfPossession);
+        assert_eq!(pop.dst, b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_");
+        assert_eq!(pop.pop_dst, b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_");
+    }
+
+    #[test]
+    fn test_ciphersuites() {
+        for scheme in [
+            CiphersuiteScheme::Basic,
+            CiphersuiteScheme::MessageAugmentation,
+            CiphersuiteScheme::ProofOfPossession,
+        ] {
+            test_ciphersuite::<BLSSignatureG1<Bls12_381, Sswu>>(scheme);
+            test_ciphersuite::<BLSSignatureG2<Bls12_381, Sswu>>(scheme);
+            test_ciphersuite::<BLSSignatureG2<Bls12_381>>(scheme);
+        }
+    }
+
+    #[test]
+    fn test_key_validate() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<BLSSignatureG2<Bls12_381, Sswu>>::setup(rng).unwrap();
+        let ciphersuite = BLSCiphersuite::new(srs, CiphersuiteScheme::ProofOfPossession);
+        let keypair = ciphersuite.generate_keypair(rng).unwrap();
+        let message = b"hello";
+        let signature = ciphersuite.sign(rng, &keypair.0, message).unwrap();
+
+        // The identity signs every message under the identity public key.
+        let (identity, identity_sig) = (G1Affine::zero(), G2Affine::zero());
+        assert!(matches!(
+            ciphersuite.verify(&identity, message, &identity_sig),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.pop_verify(&identity, &identity_sig),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.aggregate_verify(
+                &[&keypair.1, &identity],
+                &[message, b"other"],
+                &signature
+            ),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.batch_verify(
+                rng,
+                &[&keypair.1, &identity],
+                &[message, message],
+                &[&signature, &identity_sig]
+            ),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.batch_verify_deterministic(&[&identity], &[message], &[&identity_sig]),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+
+        // A point of the curve outside of the prime order subgroup.
+        let outside = (1u64..)
+            .find_map(|x| G1Affine::get_point_from_x(Fq::from(x), false))
+            .unwrap();
+        assert!(!is_in_correct_subgroup_assuming_on_curve(&outside));
+        assert!(matches!(
+            ciphersuite.verify(&outside, message, &signature),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.pop_verify(&outside, &signature),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            ciphersuite.batch_verify_with_scalars(
+                rng,
+                BatchScalars::Small(128),
+                &[&outside],
+                &[message],
+                &[&signature]
+            ),
+            Err(SignatureError::InvalidPublicKey)
+        ));
+    }
+
+    fn test_ciphersuite<B: BLSSignatureScheme>(scheme: CiphersuiteScheme) {
+        let rng = &mut thread_rng();
+        let srs = SRS::<B>::setup(rng).unwrap();
+        let ciphersuite = BLSCiphersuite::new(srs, scheme);
+        let keypair1 = ciphersuite.generate_keypair(rng).unwrap();
+        let keypair2 = ciphersuite.generate_keypair(rng).unwrap();
+        let message1 = b"hello";
+        let message2 = b"hello2";
+
+        let signature1 = ciphersuite.sign(rng, &keypair1.0, message1).unwrap();
+        let signature2 = ciphersuite.sign(rng, &keypair2.0, message2).unwrap();
+        ciphersuite
+            .verify(&keypair1.1, message1, &signature1)
+            .unwrap();
+        ciphersuite
+            .verify(&keypair1.1, message2, &signature1)
+            .unwrap_err();
+        ciphersuite
+            .batch_verify(
+                rng,
+                &[&keypair1.1, &keypair2.1],
+                &[message1, message2],
+                &[&signature1, &signature2],
+            )
+            .unwrap();
+        ciphersuite
+            .batch_verify_deterministic(
+                &[&keypair1.1, &keypair2.1],
+                &[message1, message2],
+                &[&signature2, &signature1],
+            )
+            .unwrap_err();
+
+        let aggregated_sig = ciphersuite
+            .aggregate_signatures(&[&signature1, &signature2])
+            .unwrap();
+        ciphersuite
+            .aggregate_verify(
+                &[&keypair1.1, &keypair2.1],
+                &[message1, message2],
+                &aggregated_sig,
+            )
+            .unwrap();
+        ciphersuite
+            .aggregate_verify(
+                &[&keypair1.1, &keypair2.1],
+                &[message2, message1],
+                &aggregated_sig,
+            )
+            .unwrap_err();
+
+        // Signatures under another tag don't verify.
+        let mut other_dst = ciphersuite.clone();
+        other_dst.dst = b"OTHER_DST".to_vec();
+        other_dst
+            .verify(&keypair1.1, message1, &signature1)
+            .unwrap_err();
+
+        // Signatures on the same message only aggregate without the basic scheme.
+        let signature2 = ciphersuite.sign(rng, &keypair2.0, message1).unwrap();
+        let aggregated_sig = ciphersuite
+            .aggregate_signatures(&[&signature1, &signature2])
+            .unwrap();
+        let result = ciphersuite.aggregate_verify(
+            &[&keypair1.1, &keypair2.1],
+            &[message1, message1],
+            &aggregated_sig,
+        );
+        match scheme {
+            CiphersuiteScheme::Basic => {
+                assert!(matches!(result, Err(SignatureError::DuplicateMessages)))
+            }
+            _ => result.unwrap(),
+        }
+
+        let aggregated_pk = ciphersuite.aggregate_public_keys(&[&keypair1.1, &keypair2.1]);
+        match scheme {
+            CiphersuiteScheme::ProofOfPossession => {
+                let proof1 = ciphersuite.pop_prove(&keypair1.0).unwrap();
+                let proof2 = ciphersuite.pop_prove(&keypair2.0).unwrap();
+                ciphersuite.pop_verify(&keypair1.1, &proof1).unwrap();
+                ciphersuite.pop_verify(&keypair2.1, &proof2).unwrap();
+                ciphersuite.pop_verify(&keypair1.1, &proof2).unwrap_err();
+                ciphersuite
+                    .verify(&aggregated_pk.unwrap(), message1, &aggregated_sig)
+                    .unwrap();
+                ciphersuite
+                    .fast_aggregate_verify(&[&keypair1.1, &keypair2.1], message1, &aggregated_sig)
+                    .unwrap();
+            }
+            _ => {
+                assert!(matches!(
+                    aggregated_pk,
+                    Err(SignatureError::PublicKeyAggregation)
+                ));
+                assert!(matches!(
+                    ciphersuite.fast_aggregate_verify(
+                        &[&keypair1.1, &keypair2.1],
+                        message1,
+                        &aggregated_sig
+                    ),
+                    Err(SignatureError::PublicKeyAggregation)
+                ));
+            }
+        }
+    }
+}
@@ -1,17 +1,33 @@
 use crate::signature::{
-    scheme::{AggregatableSignatureScheme, BatchVerifiableSignatureScheme, SignatureScheme},
-    utils::{errors::SignatureError, hash::hash_to_group},
+    scheme::{
+        AggregatableSignatureScheme, BatchVerifiableSignatureScheme,
+        HalfAggregatableSignatureScheme, SignatureScheme,
+    },
+    utils::{
+        batch::{batch_challenge, powers, BatchScalars},
+        errors::SignatureError,
+        group::is_in_correct_subgroup_assuming_on_curve,
+        hash_to_curve::{HashToCurve, TryAndIncrement},
+    },
 };
 use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
 use rand::Rng;
 use srs::SRS;
-use std::{fmt::Debug, ops::Neg};
+use std::{collections::BTreeSet, fmt::Debug, marker::PhantomData, ops::Neg};
 
+pub mod ciphersuite;
 pub mod srs;
 
 const PERSONALIZATION: &[u8] = b"BLSSIGNA";
 
+type PairingProducts<B> = Vec<
+    Vec<(
+        <B as BLSSignatureScheme>::PublicKeyGroup,
+        <B as BLSSignatureScheme>::SignatureGroup,
+    )>,
+>;
+
 pub trait BLSSignatureScheme: Debug + Clone + PartialEq {
     type PublicKeyGroup: AffineCurve;
     type SignatureGroup: AffineCurve<
@@ -26,6 +42,8 @@ pub trait BLSSignatureScheme: Debug + Clone + PartialEq {
         + Into<Self::SignatureGroup>
         + std::ops::MulAssign<<Self::PublicKeyGroup as AffineCurve>::ScalarField>;
     type TargetGroup: Field;
+    /// How messages are hashed to the signature group.
+    type Hasher: HashToCurve<Self::SignatureGroup>;
 
     fn product_of_pairings(
         pairs: Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>,
@@ -34,6 +52,15 @@ pub trait BLSSignatureScheme: Debug + Clone + PartialEq {
     fn batch_product_of_pairings_is_one<R: Rng>(
         rng: &mut R,
         pairs: Vec<Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>>,
+    ) -> bool {
+        let weights = BatchScalars::Full.sample(rng, pairs.len());
+        Self::batch_product_of_pairings_is_one_weighted(&weights, pairs)
+    }
+
+    /// Checks all the products of pairings at once, raising the `i`-th product to `weights[i]`.
+    fn batch_product_of_pairings_is_one_weighted(
+        weights: &[<Self::PublicKeyGroup as AffineCurve>::ScalarField],
+        pairs: Vec<Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>>,
     ) -> bool;
 }
 
@@ -73,10 +100,7 @@ impl<B: BLSSignatureScheme> SignatureScheme for BLSSignature<B> {
         sk: &Self::Secret,
         message: &[u8],
     ) -> Result<Self::Signature, SignatureError> {
-        let hashed_message = hash_to_group::<B::SignatureGroup>(PERSONALIZATION, message)?;
-        let signature = hashed_message.mul(sk.into_repr());
-        let sig = signature.into_affine();
-        Ok(sig)
+        self.core_sign(PERSONALIZATION, sk, message)
     }
 
     fn verify(
@@ -85,18 +109,7 @@ impl<B: BLSSignatureScheme> SignatureScheme for BLSSignature<B> {
         message: &[u8],
         signature: &Self::Signature,
     ) -> Result<(), SignatureError> {
-        let hashed_message = hash_to_group::<B::SignatureGroup>(PERSONALIZATION, message)?;
-
-        let eq = vec![
-            (*public_key, hashed_message.into_affine()),
-            (self.srs.g_public_key.neg(), *signature),
-        ];
-        let sig = B::product_of_pairings(eq);
-        if !sig.is_one() {
-            return Err(SignatureError::BLSVerify);
-        }
-
-        Ok(())
+        self.core_verify(PERSONALIZATION, public_key, message, signature)
     }
 }
 
@@ -121,13 +134,206 @@ impl<B: BLSSignatureScheme> AggregatableSignatureScheme for BLSSignature<B> {
 }
 
 impl<B: BLSSignatureScheme> BatchVerifiableSignatureScheme for BLSSignature<B> {
-    fn batch_verify<R: Rng>(
+    fn batch_verify_with_scalars<R: Rng>(
         &self,
         rng: &mut R,
+        scalars: BatchScalars,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let pairs = self.batch_pairs(PERSONALIZATION, public_keys, messages, signatures)?;
+        let weights = scalars.sample(rng, pairs.len());
+        if !B::batch_product_of_pairings_is_one_weighted(&weights, pairs) {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+
+    fn batch_verify_deterministic(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let pairs = self.batch_pairs(PERSONALIZATION, public_keys, messages, signatures)?;
+        let alpha = batch_challenge::<_, Self, _>(&self.srs, public_keys, messages, signatures)?;
+        let weights = powers(alpha, pairs.len());
+        if !B::batch_product_of_pairings_is_one_weighted(&weights, pairs) {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+}
+
+/// BLS signatures aggregate completely, so nothing of each signature is kept and the response is
+/// the aggregate signature, checked by `aggregate_verify`.
+impl<B: BLSSignatureScheme> HalfAggregatableSignatureScheme for BLSSignature<B> {
+    type Commitment = PhantomData<B::SignatureGroup>;
+    type Response = B::SignatureGroup;
+
+    fn commitment(_: &Self::Signature) -> Self::Commitment {
+        PhantomData
+    }
+
+    fn half_aggregate(
+        &self,
         public_keys: &[&Self::PublicKey],
         messages: &[&[u8]],
         signatures: &[&Self::Signature],
+    ) -> Result<Self::Response, SignatureError> {
+        if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
+            return Err(SignatureError::BatchVerification(
+                public_keys.len(),
+                messages.len(),
+                signatures.len(),
+            ));
+        }
+        self.aggregate_signatures(signatures)
+    }
+
+    fn verify_half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        commitments: &[&Self::Commitment],
+        response: &Self::Response,
+    ) -> Result<(), SignatureError> {
+        if public_keys.len() != messages.len() || public_keys.len() != commitments.len() {
+            return Err(SignatureError::BatchVerification(
+                public_keys.len(),
+                messages.len(),
+                commitments.len(),
+            ));
+        }
+        self.aggregate_verify(public_keys, messages, response)
+    }
+}
+
+impl<B: BLSSignatureScheme> BLSSignature<B> {
+    /// Verifies a signature aggregated from signatures of each public key on its message, with a
+    /// single product of pairings. The messages must be distinct, as otherwise a rogue public key
+    /// could cancel the others.
+    pub fn aggregate_verify(
+        &self,
+        public_keys: &[&B::PublicKeyGroup],
+        messages: &[&[u8]],
+        signature: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        if messages.iter().collect::<BTreeSet<_>>().len() != messages.len() {
+            return Err(SignatureError::DuplicateMessages);
+        }
+        self.core_aggregate_verify(PERSONALIZATION, public_keys, messages, signature)
+    }
+
+    /// Verifies a signature aggregated from signatures of all the public keys on the same message.
+    /// The public keys must be known not to be rogue, e.g. from proofs of possession.
+    pub fn fast_aggregate_verify(
+        &self,
+        public_keys: &[&B::PublicKeyGroup],
+        message: &[u8],
+        signature: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        if public_keys.is_empty() {
+            return Err(SignatureError::BLSVerify);
+        }
+        let aggregated_public_key = self.aggregate_public_keys(public_keys)?;
+        self.verify(&aggregated_public_key, message, signature)
+    }
+
+    // Checks that the product of the pairings of the public keys with their hashed messages is the
+    // pairing of the generator with the signature.
+    fn core_aggregate_verify(
+        &self,
+        dst: &[u8],
+        public_keys: &[&B::PublicKeyGroup],
+        messages: &[&[u8]],
+        signature: &B::SignatureGroup,
+    ) -> Result<(), SignatureError> {
+        if public_keys.len() != messages.len() {
+            return Err(SignatureError::AggregateVerification(
+                public_keys.len(),
+                messages.len(),
+            ));
+        }
+        if public_keys.is_empty() {
+            return Err(SignatureError::BLSVerify);
+        }
+        Self::signature_validate(signature)?;
+        let mut pairs = vec![(self.srs.g_public_key.neg(), *signature)];
+        for (public_key, message) in public_keys.iter().zip(messages.iter()) {
+            Self::key_validate(public_key)?;
+            let hashed_message = B::Hasher::hash_to_curve(dst, message)?;
+            pairs.push((**public_key, hashed_message.into_affine()));
+        }
+        if !B::product_of_pairings(pairs).is_one() {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+
+    // Signs with messages hashed under the domain separation tag `dst`.
+    fn core_sign(
+        &self,
+        dst: &[u8],
+        sk: &<B::PublicKeyGroup as AffineCurve>::ScalarField,
+        message: &[u8],
+    ) -> Result<B::SignatureGroup, SignatureError> {
+        let hashed_message = B::Hasher::hash_to_curve(dst, message)?;
+        Ok(hashed_message.mul(sk.into_repr()).into_affine())
+    }
+
+    // Verifies a signature produced by `core_sign` with the same `dst`.
+    fn core_verify(
+        &self,
+        dst: &[u8],
+        public_key: &B::PublicKeyGroup,
+        message: &[u8],
+        signature: &B::SignatureGroup,
     ) -> Result<(), SignatureError> {
+        Self::key_validate(public_key)?;
+        Self::signature_validate(signature)?;
+        let hashed_message = B::Hasher::hash_to_curve(dst, message)?;
+
+        let eq = vec![
+            (*public_key, hashed_message.into_affine()),
+            (self.srs.g_public_key.neg(), *signature),
+        ];
+        let sig = B::product_of_pairings(eq);
+        if !sig.is_one() {
+            return Err(SignatureError::BLSVerify);
+        }
+
+        Ok(())
+    }
+
+    // KeyValidate of the IRTF draft: with the identity as public key, the identity would be a valid
+    // signature of every message.
+    fn key_validate(public_key: &B::PublicKeyGroup) -> Result<(), SignatureError> {
+        if public_key.is_zero() || !is_in_correct_subgroup_assuming_on_curve(public_key) {
+            return Err(SignatureError::InvalidPublicKey);
+        }
+        Ok(())
+    }
+
+    fn signature_validate(signature: &B::SignatureGroup) -> Result<(), SignatureError> {
+        if !is_in_correct_subgroup_assuming_on_curve(signature) {
+            return Err(SignatureError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    // The pairing products that are one for valid signatures, one product per signature.
+    fn batch_pairs(
+        &self,
+        dst: &[u8],
+        public_keys: &[&B::PublicKeyGroup],
+        messages: &[&[u8]],
+        signatures: &[&B::SignatureGroup],
+    ) -> Result<PairingProducts<B>, SignatureError> {
         if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
             return Err(SignatureError::BatchVerification(
                 public_keys.len(),
@@ -137,7 +343,9 @@ impl<B: BLSSignatureScheme> BatchVerifiableSignatureScheme for BLSSignature<B> {
         }
         let mut pairs = vec![];
         for i in 0..public_keys.len() {
-            let hashed_message = hash_to_group::<B::SignatureGroup>(PERSONALIZATION, messages[i])?;
+            Self::key_validate(public_keys[i])?;
+            Self::signature_validate(signatures[i])?;
+            let hashed_message = B::Hasher::hash_to_curve(dst, messages[i])?;
 
             let eq = vec![
                 (*public_keys[i], hashed_message.into_affine()),
@@ -146,24 +354,21 @@ impl<B: BLSSignatureScheme> BatchVerifiableSignatureScheme for BLSSignature<B> {
 
             pairs.push(eq);
         }
-
-        if !B::batch_product_of_pairings_is_one(rng, pairs) {
-            return Err(SignatureError::BLSVerify);
-        }
-
-        Ok(())
+        Ok(pairs)
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct BLSSignatureG1<E: PairingEngine> {
+pub struct BLSSignatureG1<E: PairingEngine, H: HashToCurve<E::G1Affine> = TryAndIncrement> {
     pairing_type: std::marker::PhantomData<E>,
+    hash_to_curve: std::marker::PhantomData<H>,
 }
-impl<E: PairingEngine> BLSSignatureScheme for BLSSignatureG1<E> {
+impl<E: PairingEngine, H: HashToCurve<E::G1Affine>> BLSSignatureScheme for BLSSignatureG1<E, H> {
     type PublicKeyGroup = E::G2Affine;
     type SignatureGroup = E::G1Affine;
     type SignatureGroupProjective = E::G1Projective;
     type TargetGroup = E::Fqk;
+    type Hasher = H;
 
     fn product_of_pairings(
         pairs: Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>,
@@ -175,21 +380,17 @@ impl<E: PairingEngine> BLSSignatureScheme for BLSSignatureG1<E> {
         E::product_of_pairings(pairs.iter())
     }
 
-    fn batch_product_of_pairings_is_one<R: Rng>(
-        rng: &mut R,
+    fn batch_product_of_pairings_is_one_weighted(
+        weights: &[E::Fr],
         pairs_list: Vec<Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>>,
     ) -> bool {
-        let alpha = E::Fr::rand(rng);
-
-        let mut current_alpha = E::Fr::one();
         let mut batch_elements = vec![];
         let mut other_elements = vec![];
-        for pairs in pairs_list {
+        for (pairs, weight) in pairs_list.into_iter().zip(weights.iter()) {
             for pair in pairs {
-                batch_elements.push(pair.1.mul(current_alpha.into_repr()));
+                batch_elements.push(pair.1.mul(weight.into_repr()));
                 other_elements.push(pair.0);
             }
-            current_alpha *= &alpha;
         }
         let batch_elements_affine =
             E::G1Projective::batch_normalization_into_affine(&batch_elements);
@@ -203,14 +404,16 @@ impl<E: PairingEngine> BLSSignatureScheme for BLSSignatureG1<E> {
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct BLSSignatureG2<E: PairingEngine> {
+pub struct BLSSignatureG2<E: PairingEngine, H: HashToCurve<E::G2Affine> = TryAndIncrement> {
     pairing_type: std::marker::PhantomData<E>,
+    hash_to_curve: std::marker::PhantomData<H>,
 }
-impl<E: PairingEngine> BLSSignatureScheme for BLSSignatureG2<E> {
+impl<E: PairingEngine, H: HashToCurve<E::G2Affine>> BLSSignatureScheme for BLSSignatureG2<E, H> {
     type PublicKeyGroup = E::G1Affine;
     type SignatureGroup = E::G2Affine;
     type SignatureGroupProjective = E::G2Projective;
     type TargetGroup = E::Fqk;
+    type Hasher = H;
 
     fn product_of_pairings(
         pairs: Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>,
@@ -222,21 +425,17 @@ impl<E: PairingEngine> BLSSignatureScheme for BLSSignatureG2<E> {
         E::product_of_pairings(pairs.iter())
     }
 
-    fn batch_product_of_pairings_is_one<R: Rng>(
-        rng: &mut R,
+    fn batch_product_of_pairings_is_one_weighted(
+        weights: &[E::Fr],
         pairs_list: Vec<Vec<(Self::PublicKeyGroup, Self::SignatureGroup)>>,
     ) -> bool {
-        let alpha = E::Fr::rand(rng);
-
-        let mut current_alpha = E::Fr::one();
         let mut batch_elements = vec![];
         let mut other_elements = vec![];
-        for pairs in pairs_list {
+        for (pairs, weight) in pairs_list.into_iter().zip(weights.iter()) {
             for pair in pairs {
-                batch_elements.push(pair.0.mul(current_alpha.into_repr()));
+                batch_elements.push(pair.0.mul(weight.into_repr()));
                 other_elements.push(pair.1);
             }
-            current_alpha *= &alpha;
         }
         let batch_elements_affine =
             E::G1Projective::batch_normalization_into_affine(&batch_elements);
@@ -256,7 +455,7 @@ mod test {
     use super::{BLSSignatureG1, BLSSignatureG2, BLSSignatureScheme, SRS};
     use crate::signature::{
         scheme::{AggregatableSignatureScheme, SignatureScheme},
-        utils::tests::check_serialization,
+        utils::{errors::SignatureError, hash_to_curve::Sswu, tests::check_serialization},
     };
 
     use crate::signature::{bls::BLSSignature, scheme::BatchVerifiableSignatureScheme};
@@ -272,6 +471,21 @@ mod test {
         test_simple_sig::<BLSSignatureG2<Bls12_381>>();
     }
 
+    #[test]
+    fn test_simple_sig_sswu() {
+        test_simple_sig::<BLSSignatureG1<Bls12_381, Sswu>>();
+        test_simple_sig::<BLSSignatureG2<Bls12_381, Sswu>>();
+        test_aggregated_sig::<BLSSignatureG1<Bls12_381, Sswu>>();
+        test_aggregated_sig::<BLSSignatureG2<Bls12_381, Sswu>>();
+        test_simple_sig_batch::<BLSSignatureG2<Bls12_381, Sswu>>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_simple_sig_wrong_message_sswu() {
+        test_simple_sig_wrong_message::<BLSSignatureG2<Bls12_381, Sswu>>();
+    }
+
     fn test_simple_sig<B: BLSSignatureScheme>() {
         let rng = &mut thread_rng();
         let srs = SRS::<B>::setup(rng).unwrap();
@@ -367,6 +581,65 @@ mod test {
             .unwrap();
     }
 
+    #[test]
+    fn test_aggregate_verify_g1() {
+        test_aggregate_verify::<BLSSignatureG1<Bls12_381>>();
+    }
+
+    #[test]
+    fn test_aggregate_verify_g2() {
+        test_aggregate_verify::<BLSSignatureG2<Bls12_381>>();
+    }
+
+    fn test_aggregate_verify<B: BLSSignatureScheme>() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<B>::setup(rng).unwrap();
+        let bls = BLSSignature { srs };
+        let keypair1 = bls.generate_keypair(rng).unwrap();
+        let keypair2 = bls.generate_keypair(rng).unwrap();
+        let message1 = b"hello";
+        let message2 = b"hello2";
+
+        let signature1 = bls.sign(rng, &keypair1.0, &message1[..]).unwrap();
+        let signature2 = bls.sign(rng, &keypair2.0, &message2[..]).unwrap();
+        let aggregated_sig = bls
+            .aggregate_signatures(&[&signature1, &signature2])
+            .unwrap();
+        bls.aggregate_verify(
+            &[&keypair1.1, &keypair2.1],
+            &[&message1[..], &message2[..]],
+            &aggregated_sig,
+        )
+        .unwrap();
+        bls.aggregate_verify(
+            &[&keypair1.1, &keypair2.1],
+            &[&message2[..], &message1[..]],
+            &aggregated_sig,
+        )
+        .unwrap_err();
+        bls.aggregate_verify(&[&keypair1.1], &[&message1[..]], &aggregated_sig)
+            .unwrap_err();
+
+        let signature2 = bls.sign(rng, &keypair2.0, &message1[..]).unwrap();
+        let aggregated_sig = bls
+            .aggregate_signatures(&[&signature1, &signature2])
+            .unwrap();
+        assert!(matches!(
+            bls.aggregate_verify(
+                &[&keypair1.1, &keypair2.1],
+                &[&message1[..], &message1[..]],
+                &aggregated_sig,
+            ),
+            Err(SignatureError::DuplicateMessages)
+        ));
+        bls.fast_aggregate_verify(&[&keypair1.1, &keypair2.1], &message1[..], &aggregated_sig)
+            .unwrap();
+        bls.fast_aggregate_verify(&[&keypair1.1, &keypair2.1], &message2[..], &aggregated_sig)
+            .unwrap_err();
+        bls.fast_aggregate_verify(&[&keypair1.1], &message1[..], &aggregated_sig)
+            .unwrap_err();
+    }
+
     #[test]
     #[should_panic]
     fn test_aggregated_sig_wrong_pk_g1() {
@@ -460,6 +733,18 @@ mod test {
             &[&signature, &signature2],
         )
         .unwrap();
+        bls.batch_verify_deterministic(
+            &[&keypair.1, &keypair2.1],
+            &[&message[..], &message2[..]],
+            &[&signature, &signature2],
+        )
+        .unwrap();
+        bls.batch_verify_deterministic(
+            &[&keypair.1, &keypair2.1],
+            &[&message[..], &message2[..]],
+            &[&signature2, &signature],
+        )
+        .unwrap_err();
     }
 
     #[test]
@@ -479,7 +764,7 @@ mod test {
         let signature = bls.sign(rng, &keypair.0, &message[..]).unwrap();
 
         check_serialization(srs.clone());
-        check_serialization(keypair.clone());
-        check_serialization(signature.clone());
+        check_serialization(keypair);
+        check_serialization(signature);
     }
 }
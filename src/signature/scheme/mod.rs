@@ -1,4 +1,7 @@
-use crate::signature::utils::errors::SignatureError;
+use crate::signature::utils::{
+    batch::{batch_challenge_rng, BatchScalars},
+    errors::SignatureError,
+};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use rand::Rng;
 use std::fmt::Debug;
@@ -48,5 +51,62 @@ pub trait BatchVerifiableSignatureScheme: SignatureScheme {
         public_keys: &[&Self::PublicKey],
         messages: &[&[u8]],
         signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        self.batch_verify_with_scalars(rng, BatchScalars::Full, public_keys, messages, signatures)
+    }
+
+    /// Like `batch_verify`, weighting the signatures with the given kind of random scalars.
+    fn batch_verify_with_scalars<R: Rng>(
+        &self,
+        rng: &mut R,
+        scalars: BatchScalars,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError>;
+
+    /// Like `batch_verify`, with the batching scalars derived from a hash of the public keys,
+    /// messages and signatures instead of an rng. Schemes can override it to also hash their SRS.
+    fn batch_verify_deterministic(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let mut rng = batch_challenge_rng::<Self>(public_keys, messages, signatures)?;
+        self.batch_verify_with_scalars(
+            &mut rng,
+            BatchScalars::Full,
+            public_keys,
+            messages,
+            signatures,
+        )
+    }
+}
+
+/// A scheme whose signatures can be half-aggregated: every signature keeps a commitment, and the
+/// rest of the signatures is compressed into a single response.
+pub trait HalfAggregatableSignatureScheme: BatchVerifiableSignatureScheme {
+    type Commitment: Clone + CanonicalSerialize + CanonicalDeserialize;
+    type Response: Clone + CanonicalSerialize + CanonicalDeserialize;
+
+    /// Returns the part of the signature that half-aggregation keeps.
+    fn commitment(signature: &Self::Signature) -> Self::Commitment;
+
+    /// Compresses the signatures into one response, bound to all the public keys, messages and
+    /// commitments.
+    fn half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<Self::Response, SignatureError>;
+
+    fn verify_half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        commitments: &[&Self::Commitment],
+        response: &Self::Response,
     ) -> Result<(), SignatureError>;
 }
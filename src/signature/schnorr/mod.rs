@@ -1,9 +1,14 @@
 use crate::signature::{
-    scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
-    utils::{errors::SignatureError, hash::hash_to_field},
+    scheme::{BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme, SignatureScheme},
+    utils::{
+        batch::{batch_challenge, powers, BatchScalars},
+        errors::SignatureError,
+        hash::hash_to_field,
+    },
 };
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, ProjectiveCurve};
-use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
 use rand::Rng;
 use srs::SRS;
 use std::{fmt::Debug, ops::Neg};
@@ -11,6 +16,8 @@ use std::{fmt::Debug, ops::Neg};
 pub mod srs;
 
 const PERSONALIZATION: &[u8] = b"SCHSIGNA";
+const HALF_AGGREGATION_PERSONALIZATION: &[u8] = b"SCHHAGGR";
+const NONCE_PERSONALIZATION: &[u8] = b"SCHNONCE";
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct SchnorrSignature<C: AffineCurve> {
@@ -42,26 +49,15 @@ impl<C: AffineCurve> SignatureScheme for SchnorrSignature<C> {
         Ok((*sk, self.srs.g_public_key.mul(sk.into_repr()).into_affine()))
     }
 
+    /// Signs with a nonce derived from the secret key and the message, so that the same key and
+    /// message always give the same signature and a bad rng can't leak the key. The rng is unused.
     fn sign<R: Rng>(
         &self,
-        rng: &mut R,
+        _: &mut R,
         sk: &Self::Secret,
         message: &[u8],
     ) -> Result<Self::Signature, SignatureError> {
-        let v = C::ScalarField::rand(rng);
-        let v_g = self.srs.g_public_key.mul(v.into_repr()).into_affine();
-        let mut v_g_bytes = vec![];
-        v_g.serialize(&mut v_g_bytes)?;
-        let mut g_bytes = vec![];
-        self.srs.g_public_key.serialize(&mut g_bytes)?;
-
-        let hashed_message = hash_to_field::<C::ScalarField>(
-            PERSONALIZATION,
-            &[message, &v_g_bytes, &g_bytes].concat(),
-        )?;
-        let r = v - &(*sk * &hashed_message);
-        let sig = (v_g, r);
-        Ok(sig)
+        self.sign_with_aux(sk, message, &[])
     }
 
     fn verify(
@@ -92,12 +88,185 @@ impl<C: AffineCurve> SignatureScheme for SchnorrSignature<C> {
 }
 
 impl<C: AffineCurve> BatchVerifiableSignatureScheme for SchnorrSignature<C> {
-    fn batch_verify<R: Rng>(
+    fn batch_verify_with_scalars<R: Rng>(
         &self,
         rng: &mut R,
+        scalars: BatchScalars,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let weights = scalars.sample(rng, public_keys.len());
+        self.batch_verify_weighted(&weights, public_keys, messages, signatures)
+    }
+
+    fn batch_verify_deterministic(
+        &self,
         public_keys: &[&Self::PublicKey],
         messages: &[&[u8]],
         signatures: &[&Self::Signature],
+    ) -> Result<(), SignatureError> {
+        let alpha = batch_challenge::<_, Self, _>(&self.srs, public_keys, messages, signatures)?;
+        let weights = powers(alpha, public_keys.len());
+        self.batch_verify_weighted(&weights, public_keys, messages, signatures)
+    }
+}
+
+/// Keeps the `R` of every signature and compresses the responses into `sum_i z_i * s_i`, where
+/// the coefficients `z_i` are hashed from all the public keys, messages and `R`s.
+impl<C: AffineCurve> HalfAggregatableSignatureScheme for SchnorrSignature<C> {
+    type Commitment = C;
+    type Response = C::ScalarField;
+
+    fn commitment(signature: &Self::Signature) -> Self::Commitment {
+        signature.0
+    }
+
+    fn half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        signatures: &[&Self::Signature],
+    ) -> Result<Self::Response, SignatureError> {
+        let commitments = signatures.iter().map(|s| &s.0).collect::<Vec<_>>();
+        let coefficients =
+            self.half_aggregation_coefficients(public_keys, messages, &commitments)?;
+        Ok(signatures
+            .iter()
+            .zip(coefficients.iter())
+            .fold(C::ScalarField::zero(), |acc, (signature, z)| {
+                acc + signature.1 * z
+            }))
+    }
+
+    fn verify_half_aggregate(
+        &self,
+        public_keys: &[&Self::PublicKey],
+        messages: &[&[u8]],
+        commitments: &[&Self::Commitment],
+        response: &Self::Response,
+    ) -> Result<(), SignatureError> {
+        let coefficients =
+            self.half_aggregation_coefficients(public_keys, messages, commitments)?;
+
+        let mut g_bytes = vec![];
+        self.srs.g_public_key.serialize(&mut g_bytes)?;
+
+        let mut bases = vec![self.srs.g_public_key];
+        let mut scalars = vec![response.into_repr()];
+        for (i, z) in coefficients.iter().enumerate() {
+            let mut v_g_bytes = vec![];
+            commitments[i].serialize(&mut v_g_bytes)?;
+
+            let hashed_message = hash_to_field::<C::ScalarField>(
+                PERSONALIZATION,
+                &[messages[i], &v_g_bytes, &g_bytes].concat(),
+            )?;
+
+            bases.push(*public_keys[i]);
+            scalars.push((hashed_message * z).into_repr());
+
+            bases.push(*commitments[i]);
+            scalars.push(z.neg().into_repr());
+        }
+        let accumulated_check = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+        if !accumulated_check.is_zero() {
+            return Err(SignatureError::SchnorrVerify);
+        }
+        Ok(())
+    }
+}
+
+impl<C: AffineCurve> SchnorrSignature<C> {
+    /// Signs with a nonce derived from the secret key, the message and the auxiliary randomness
+    /// `aux`, as in BIP-340. Fresh `aux` hardens the nonce against fault and side-channel attacks,
+    /// and the nonce stays safe if `aux` is weak or repeated.
+    pub fn sign_with_aux(
+        &self,
+        sk: &C::ScalarField,
+        message: &[u8],
+        aux: &[u8],
+    ) -> Result<(C, C::ScalarField), SignatureError> {
+        let v = self.derive_nonce(sk, message, aux)?;
+        let v_g = self.srs.g_public_key.mul(v.into_repr()).into_affine();
+        let mut v_g_bytes = vec![];
+        v_g.serialize(&mut v_g_bytes)?;
+        let mut g_bytes = vec![];
+        self.srs.g_public_key.serialize(&mut g_bytes)?;
+
+        let hashed_message = hash_to_field::<C::ScalarField>(
+            PERSONALIZATION,
+            &[message, &v_g_bytes, &g_bytes].concat(),
+        )?;
+        let r = v - *sk * hashed_message;
+        let sig = (v_g, r);
+        Ok(sig)
+    }
+
+    // Hashes the secret key with everything the signature depends on, so that a nonce is only
+    // reused for the same signature.
+    fn derive_nonce(
+        &self,
+        sk: &C::ScalarField,
+        message: &[u8],
+        aux: &[u8],
+    ) -> Result<C::ScalarField, SignatureError> {
+        let public_key = self.srs.g_public_key.mul(sk.into_repr()).into_affine();
+        let mut input = vec![];
+        sk.serialize(&mut input)?;
+        aux.to_vec().serialize(&mut input)?;
+        self.srs.g_public_key.serialize(&mut input)?;
+        public_key.serialize(&mut input)?;
+        input.extend_from_slice(message);
+        hash_to_field(NONCE_PERSONALIZATION, &input)
+    }
+
+    // The coefficients of half-aggregation. They are derived from a digest of the whole statement,
+    // so that no signature can be chosen after them.
+    fn half_aggregation_coefficients(
+        &self,
+        public_keys: &[&C],
+        messages: &[&[u8]],
+        commitments: &[&C],
+    ) -> Result<Vec<C::ScalarField>, SignatureError> {
+        if public_keys.len() != messages.len() || public_keys.len() != commitments.len() {
+            return Err(SignatureError::BatchVerification(
+                public_keys.len(),
+                messages.len(),
+                commitments.len(),
+            ));
+        }
+        let mut statement = vec![];
+        self.srs.g_public_key.serialize(&mut statement)?;
+        (public_keys.len() as u64).serialize(&mut statement)?;
+        for ((public_key, message), commitment) in public_keys
+            .iter()
+            .zip(messages.iter())
+            .zip(commitments.iter())
+        {
+            public_key.serialize(&mut statement)?;
+            message.to_vec().serialize(&mut statement)?;
+            commitment.serialize(&mut statement)?;
+        }
+        let digest = hash_to_field::<C::ScalarField>(HALF_AGGREGATION_PERSONALIZATION, &statement)?;
+        let mut digest_bytes = vec![];
+        digest.serialize(&mut digest_bytes)?;
+        (0..public_keys.len() as u64)
+            .map(|i| {
+                hash_to_field(
+                    HALF_AGGREGATION_PERSONALIZATION,
+                    &[&digest_bytes[..], &i.to_le_bytes()].concat(),
+                )
+            })
+            .collect()
+    }
+
+    fn batch_verify_weighted(
+        &self,
+        weights: &[C::ScalarField],
+        public_keys: &[&C],
+        messages: &[&[u8]],
+        signatures: &[&(C, C::ScalarField)],
     ) -> Result<(), SignatureError> {
         if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
             return Err(SignatureError::BatchVerification(
@@ -107,15 +276,15 @@ impl<C: AffineCurve> BatchVerifiableSignatureScheme for SchnorrSignature<C> {
             ));
         }
 
-        let alpha = C::ScalarField::rand(rng);
-        let mut current_alpha = C::ScalarField::one();
-
         let mut g_bytes = vec![];
         self.srs.g_public_key.serialize(&mut g_bytes)?;
 
-        let mut bases = vec![];
+        // The responses are folded into a single scalar on g, so only the challenges stay full
+        // width and the commitments keep the (possibly small) weights.
+        let mut bases = vec![self.srs.g_public_key.into_projective()];
+        let mut g_scalar = C::ScalarField::zero();
         let mut scalars = vec![];
-        for i in 0..public_keys.len() {
+        for (i, weight) in weights.iter().enumerate() {
             let mut v_g_bytes = vec![];
             signatures[i].0.serialize(&mut v_g_bytes)?;
 
@@ -124,17 +293,15 @@ impl<C: AffineCurve> BatchVerifiableSignatureScheme for SchnorrSignature<C> {
                 &[messages[i], &v_g_bytes, &g_bytes].concat(),
             )?;
 
-            bases.push(self.srs.g_public_key.into_projective());
-            scalars.push((signatures[i].1 * &current_alpha).into_repr());
+            g_scalar += signatures[i].1 * weight;
 
             bases.push(public_keys[i].into_projective());
-            scalars.push((hashed_message * &current_alpha).into_repr());
+            scalars.push((hashed_message * weight).into_repr());
 
-            bases.push(signatures[i].0.into_projective());
-            scalars.push(current_alpha.neg().into_repr());
-
-            current_alpha *= &alpha;
+            bases.push(signatures[i].0.into_projective().neg());
+            scalars.push(weight.into_repr());
         }
+        scalars.insert(0, g_scalar.into_repr());
         let bases = C::Projective::batch_normalization_into_affine(&bases);
         let accumulated_check = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
         if !accumulated_check.is_zero() {
@@ -146,12 +313,16 @@ impl<C: AffineCurve> BatchVerifiableSignatureScheme for SchnorrSignature<C> {
 
 #[cfg(test)]
 mod test {
-    use ark_bls12_381::{G1Affine, G2Affine};
+    use ark_bls12_381::{Fr, G1Affine, G2Affine};
     use ark_ec::AffineCurve;
+    use ark_ff::One;
+    use ark_serialize::CanonicalSerialize;
 
     use super::{SchnorrSignature, SRS};
     use crate::signature::{
-        scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+        scheme::{
+            BatchVerifiableSignatureScheme, HalfAggregatableSignatureScheme, SignatureScheme,
+        },
         utils::tests::check_serialization,
     };
 
@@ -260,6 +431,138 @@ mod test {
                 &[&signature, &signature2],
             )
             .unwrap();
+        schnorr
+            .batch_verify_deterministic(
+                &[&keypair.1, &keypair2.1],
+                &[&message[..], &message2[..]],
+                &[&signature, &signature2],
+            )
+            .unwrap();
+        schnorr
+            .batch_verify_deterministic(
+                &[&keypair.1, &keypair2.1],
+                &[&message[..], &message2[..]],
+                &[&signature2, &signature],
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_deterministic_nonces() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<G1Affine>::setup(rng).unwrap();
+        let schnorr = SchnorrSignature { srs };
+        let keypair = schnorr.from_sk(&Fr::from(42u64)).unwrap();
+        let message = b"hello";
+
+        let signature = schnorr.sign(rng, &keypair.0, &message[..]).unwrap();
+        assert_eq!(
+            signature,
+            schnorr.sign(rng, &keypair.0, &message[..]).unwrap()
+        );
+        assert_eq!(
+            signature,
+            schnorr
+                .sign_with_aux(&keypair.0, &message[..], &[])
+                .unwrap()
+        );
+        let mut signature_bytes = vec![];
+        signature.serialize(&mut signature_bytes).unwrap();
+        assert_eq!(
+            encode_hex(&signature_bytes),
+            "5cc0f139bdf3daca9d81539be9b12ba072b6f3fafa8bd08766bff6cdf2701bf88f3d16660255d92ed285c6fcdb6e42101957cda27caa2df57866d90e89c6701e52b0fd4552af6a3b5a69f27f704fc233"
+        );
+
+        let other_message = schnorr.sign(rng, &keypair.0, &b"hello2"[..]).unwrap();
+        assert_ne!(signature.0, other_message.0);
+        let other_key = schnorr.sign(rng, &Fr::from(43u64), &message[..]).unwrap();
+        assert_ne!(signature.0, other_key.0);
+
+        let with_aux = schnorr
+            .sign_with_aux(&keypair.0, &message[..], b"aux")
+            .unwrap();
+        assert_ne!(signature.0, with_aux.0);
+        assert_eq!(
+            with_aux,
+            schnorr
+                .sign_with_aux(&keypair.0, &message[..], b"aux")
+                .unwrap()
+        );
+        schnorr.verify(&keypair.1, &message[..], &with_aux).unwrap();
+        let mut with_aux_bytes = vec![];
+        with_aux.serialize(&mut with_aux_bytes).unwrap();
+        assert_eq!(
+            encode_hex(&with_aux_bytes),
+            "dfcb08f1419258c176363e0669edfd0145548bf158cd9dc4d3fc374ed1aa5e4c58904f03624b17f50790465ceb0b220bf00df1a6b8e668ebae0b9f0229e526aee59a759219ce7d85627061ebf36ffa1a"
+        );
+    }
+
+    fn encode_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_half_aggregation_g1() {
+        test_half_aggregation::<G1Affine>();
+    }
+
+    #[test]
+    fn test_half_aggregation_g2() {
+        test_half_aggregation::<G2Affine>();
+    }
+
+    fn test_half_aggregation<C: AffineCurve>() {
+        let rng = &mut thread_rng();
+        let srs = SRS::<C>::setup(rng).unwrap();
+        let schnorr = SchnorrSignature { srs };
+        let keypairs = (0..3)
+            .map(|_| schnorr.generate_keypair(rng).unwrap())
+            .collect::<Vec<_>>();
+        let messages = [&b"hello"[..], &b"hello2"[..], &b"hello"[..]];
+        let signatures = keypairs
+            .iter()
+            .zip(messages.iter())
+            .map(|(keypair, message)| schnorr.sign(rng, &keypair.0, message).unwrap())
+            .collect::<Vec<_>>();
+        let public_keys = keypairs.iter().map(|k| &k.1).collect::<Vec<_>>();
+        let signature_refs = signatures.iter().collect::<Vec<_>>();
+        let commitments = signatures
+            .iter()
+            .map(SchnorrSignature::<C>::commitment)
+            .collect::<Vec<_>>();
+        let commitment_refs = commitments.iter().collect::<Vec<_>>();
+
+        let response = schnorr
+            .half_aggregate(&public_keys, &messages, &signature_refs)
+            .unwrap();
+        schnorr
+            .verify_half_aggregate(&public_keys, &messages, &commitment_refs, &response)
+            .unwrap();
+
+        let wrong_messages = [&b"hello"[..], &b"goodbye"[..], &b"hello"[..]];
+        schnorr
+            .verify_half_aggregate(&public_keys, &wrong_messages, &commitment_refs, &response)
+            .unwrap_err();
+        let swapped_commitments = [commitment_refs[1], commitment_refs[0], commitment_refs[2]];
+        schnorr
+            .verify_half_aggregate(&public_keys, &messages, &swapped_commitments, &response)
+            .unwrap_err();
+        schnorr
+            .verify_half_aggregate(
+                &public_keys,
+                &messages,
+                &commitment_refs,
+                &(response + C::ScalarField::one()),
+            )
+            .unwrap_err();
+        schnorr
+            .verify_half_aggregate(
+                &public_keys[..2],
+                &messages[..2],
+                &commitment_refs[..2],
+                &response,
+            )
+            .unwrap_err();
     }
 
     #[test]
@@ -279,7 +582,7 @@ mod test {
         let signature = schnorr.sign(rng, &keypair.0, &message[..]).unwrap();
 
         check_serialization(srs.clone());
-        check_serialization(keypair.clone());
-        check_serialization(signature.clone());
+        check_serialization(keypair);
+        check_serialization(signature);
     }
 }
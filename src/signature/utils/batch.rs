@@ -0,0 +1,309 @@
+use crate::signature::{
+    scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+    utils::{
+        errors::SignatureError,
+        hash::{hash_to_field, rng_from_message},
+    },
+};
+use ark_ff::{Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use rand::Rng;
+use rand_chacha::ChaChaRng;
+use std::ops::Range;
+
+const PERSONALIZATION: &[u8] = b"BATCHCHL";
+
+/// The random scalars that batch verification weights each check with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BatchScalars {
+    /// Powers of a random field element.
+    Full,
+    /// Independent random scalars of the given number of bits, between 1 and 128. A batch with an
+    /// invalid check passes with probability about `2^-bits`, and multiplying by the scalars is
+    /// faster than by field elements.
+    Small(u32),
+}
+
+impl BatchScalars {
+    pub fn sample<F: PrimeField, R: Rng>(&self, rng: &mut R, count: usize) -> Vec<F> {
+        match *self {
+            BatchScalars::Full => powers(F::rand(rng), count),
+            BatchScalars::Small(bits) => {
+                let mask = u128::MAX >> (128 - bits.clamp(1, 128));
+                (0..count)
+                    .map(|_| F::from(rng.gen::<u128>() & mask))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Returns `1, alpha, ..., alpha^(count - 1)`.
+pub fn powers<F: Field>(alpha: F, count: usize) -> Vec<F> {
+    let mut current = F::one();
+    let mut powers = vec![];
+    for _ in 0..count {
+        powers.push(current);
+        current *= &alpha;
+    }
+    powers
+}
+
+/// Derives the batching scalar of a batch verification from a hash of the whole statement, so
+/// that the verification needs no rng and gives the same result everywhere.
+pub fn batch_challenge<F: PrimeField, S: SignatureScheme, P: CanonicalSerialize>(
+    srs: &P,
+    public_keys: &[&S::PublicKey],
+    messages: &[&[u8]],
+    signatures: &[&S::Signature],
+) -> Result<F, SignatureError> {
+    let mut statement = vec![];
+    srs.serialize(&mut statement)?;
+    write_batch_statement::<S>(&mut statement, public_keys, messages, signatures)?;
+    hash_to_field(PERSONALIZATION, &statement)
+}
+
+/// Like `batch_challenge`, for schemes that don't expose their SRS or scalar field: returns an rng
+/// seeded with a hash of the public keys, messages and signatures.
+pub fn batch_challenge_rng<S: SignatureScheme>(
+    public_keys: &[&S::PublicKey],
+    messages: &[&[u8]],
+    signatures: &[&S::Signature],
+) -> Result<ChaChaRng, SignatureError> {
+    let mut statement = vec![];
+    write_batch_statement::<S>(&mut statement, public_keys, messages, signatures)?;
+    Ok(rng_from_message(PERSONALIZATION, &statement))
+}
+
+fn write_batch_statement<S: SignatureScheme>(
+    statement: &mut Vec<u8>,
+    public_keys: &[&S::PublicKey],
+    messages: &[&[u8]],
+    signatures: &[&S::Signature],
+) -> Result<(), SignatureError> {
+    (public_keys.len() as u64).serialize(&mut *statement)?;
+    for ((public_key, message), signature) in public_keys
+        .iter()
+        .zip(messages.iter())
+        .zip(signatures.iter())
+    {
+        public_key.serialize(&mut *statement)?;
+        message.to_vec().serialize(&mut *statement)?;
+        signature.serialize(&mut *statement)?;
+    }
+    Ok(())
+}
+
+/// Returns the indices of the invalid signatures, by bisecting the batch until the failing
+/// sub-batches are single signatures, which are verified individually.
+pub fn find_invalid_signatures<S: BatchVerifiableSignatureScheme, R: Rng>(
+    scheme: &S,
+    rng: &mut R,
+    scalars: BatchScalars,
+    public_keys: &[&S::PublicKey],
+    messages: &[&[u8]],
+    signatures: &[&S::Signature],
+) -> Result<Vec<usize>, SignatureError> {
+    if public_keys.len() != messages.len() || public_keys.len() != signatures.len() {
+        return Err(SignatureError::BatchVerification(
+            public_keys.len(),
+            messages.len(),
+            signatures.len(),
+        ));
+    }
+
+    bisect_failures(public_keys.len(), |range| {
+        Ok(if range.len() == 1 {
+            scheme
+                .verify(
+                    public_keys[range.start],
+                    messages[range.start],
+                    signatures[range.start],
+                )
+                .is_ok()
+        } else {
+            scheme
+                .batch_verify_with_scalars(
+                    rng,
+                    scalars,
+                    &public_keys[range.clone()],
+                    &messages[range.clone()],
+                    &signatures[range],
+                )
+                .is_ok()
+        })
+    })
+}
+
+/// Returns the indices in `0..len` that fail `check`, where `check` tells whether all the
+/// indices of a range pass. Failing ranges are split in half until they are single indices.
+pub fn bisect_failures<E, F: FnMut(Range<usize>) -> Result<bool, E>>(
+    len: usize,
+    mut check: F,
+) -> Result<Vec<usize>, E> {
+    let mut failures = vec![];
+    let mut ranges = vec![(0, len)];
+    while let Some((start, end)) = ranges.pop() {
+        if start == end || check(start..end)? {
+            continue;
+        }
+        if end - start == 1 {
+            failures.push(start);
+            continue;
+        }
+        let middle = start + (end - start) / 2;
+        ranges.push((middle, end));
+        ranges.push((start, middle));
+    }
+    Ok(failures)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_invalid_signatures, BatchScalars};
+    use crate::signature::{
+        scheme::{BatchVerifiableSignatureScheme, SignatureScheme},
+        schnorr::{srs::SRS, SchnorrSignature},
+        utils::errors::SignatureError,
+    };
+    use ark_bls12_381::{Fr, G1Affine, G1Projective};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::{One, UniformRand};
+    use rand::{thread_rng, Rng};
+
+    // Schnorr signatures relying on the default `batch_verify_deterministic`.
+    #[derive(Clone, Debug, PartialEq)]
+    struct DefaultDeterministic(SchnorrSignature<G1Affine>);
+
+    impl SignatureScheme for DefaultDeterministic {
+        type SRS = SRS<G1Affine>;
+        type Secret = Fr;
+        type PublicKey = G1Affine;
+        type Signature = (G1Affine, Fr);
+
+        fn from_srs(srs: Self::SRS) -> Result<Self, SignatureError> {
+            Ok(Self(SchnorrSignature::from_srs(srs)?))
+        }
+
+        fn generate_keypair<R: Rng>(
+            &self,
+            rng: &mut R,
+        ) -> Result<(Self::Secret, Self::PublicKey), SignatureError> {
+            self.0.generate_keypair(rng)
+        }
+
+        fn from_sk(
+            &self,
+            sk: &Self::Secret,
+        ) -> Result<(Self::Secret, Self::PublicKey), SignatureError> {
+            self.0.from_sk(sk)
+        }
+
+        fn sign<R: Rng>(
+            &self,
+            rng: &mut R,
+            sk: &Self::Secret,
+            message: &[u8],
+        ) -> Result<Self::Signature, SignatureError> {
+            self.0.sign(rng, sk, message)
+        }
+
+        fn verify(
+            &self,
+            public_key: &Self::PublicKey,
+            message: &[u8],
+            signature: &Self::Signature,
+        ) -> Result<(), SignatureError> {
+            self.0.verify(public_key, message, signature)
+        }
+    }
+
+    impl BatchVerifiableSignatureScheme for DefaultDeterministic {
+        fn batch_verify_with_scalars<R: Rng>(
+            &self,
+            rng: &mut R,
+            scalars: BatchScalars,
+            public_keys: &[&Self::PublicKey],
+            messages: &[&[u8]],
+            signatures: &[&Self::Signature],
+        ) -> Result<(), SignatureError> {
+            self.0
+                .batch_verify_with_scalars(rng, scalars, public_keys, messages, signatures)
+        }
+    }
+
+    #[test]
+    fn test_find_invalid_signatures() {
+        let rng = &mut thread_rng();
+        let schnorr = SchnorrSignature {
+            srs: SRS {
+                g_public_key: G1Projective::rand(rng).into_affine(),
+            },
+        };
+        let messages = (0..7u8).map(|i| vec![i]).collect::<Vec<_>>();
+        let mut public_keys = vec![];
+        let mut signatures = vec![];
+        for message in messages.iter() {
+            let (sk, pk) = schnorr.generate_keypair(rng).unwrap();
+            public_keys.push(pk);
+            signatures.push(schnorr.sign(rng, &sk, message).unwrap());
+        }
+        signatures.swap(2, 5);
+        signatures[6] = signatures[0];
+
+        let invalid = find_invalid_signatures(
+            &schnorr,
+            rng,
+            BatchScalars::Small(128),
+            &public_keys.iter().collect::<Vec<_>>(),
+            &messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>(),
+            &signatures.iter().collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(invalid, vec![2, 5, 6]);
+    }
+
+    #[test]
+    fn test_default_batch_verify_deterministic() {
+        let rng = &mut thread_rng();
+        let scheme = DefaultDeterministic::from_srs(SRS::setup(rng).unwrap()).unwrap();
+        let messages = (0..3u8).map(|i| vec![i]).collect::<Vec<_>>();
+        let mut public_keys = vec![];
+        let mut signatures = vec![];
+        for message in messages.iter() {
+            let (sk, pk) = scheme.generate_keypair(rng).unwrap();
+            public_keys.push(pk);
+            signatures.push(scheme.sign(rng, &sk, message).unwrap());
+        }
+        let public_keys = public_keys.iter().collect::<Vec<_>>();
+        let messages = messages.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+
+        scheme
+            .batch_verify_deterministic(
+                &public_keys,
+                &messages,
+                &signatures.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        signatures.swap(0, 1);
+        scheme
+            .batch_verify_deterministic(
+                &public_keys,
+                &messages,
+                &signatures.iter().collect::<Vec<_>>(),
+            )
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_batch_scalars() {
+        let rng = &mut thread_rng();
+        let full = BatchScalars::Full.sample::<Fr, _>(rng, 4);
+        assert_eq!(full[0], Fr::one());
+        assert_eq!(full[3], full[1] * full[2]);
+
+        let small = BatchScalars::Small(8).sample::<Fr, _>(rng, 100);
+        assert!(small.iter().all(|s| *s < Fr::from(256u64)));
+        assert!(small.iter().any(|s| *s != small[0]));
+    }
+}
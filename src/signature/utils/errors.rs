@@ -41,4 +41,16 @@ pub enum SignatureError {
     SerializationError(#[from] SerializationError),
     #[error("Different lengths in batch verification: {0}, {1}, {2}")]
     BatchVerification(usize, usize, usize),
+    #[error("Cannot expand a message to {0} bytes")]
+    ExpandMessageLength(usize),
+    #[error("Messages of a basic BLS aggregate signature must be distinct")]
+    DuplicateMessages,
+    #[error("Public keys can only be aggregated with proofs of possession")]
+    PublicKeyAggregation,
+    #[error("Different numbers of public keys and messages: {0}, {1}")]
+    AggregateVerification(usize, usize),
+    #[error("Public key is the identity or not in the prime order subgroup")]
+    InvalidPublicKey,
+    #[error("Signature is not in the prime order subgroup")]
+    InvalidSignature,
 }
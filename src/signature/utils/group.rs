@@ -0,0 +1,11 @@
+use ark_ec::AffineCurve;
+use ark_ff::{FpParameters, PrimeField, Zero};
+
+/// Checks that a point of the curve is in the prime order subgroup, by multiplying it by the
+/// group order. ark 0.2 only exposes this check on concrete short Weierstrass points, not through
+/// `AffineCurve`.
+pub fn is_in_correct_subgroup_assuming_on_curve<C: AffineCurve>(point: &C) -> bool {
+    point
+        .mul(<C::ScalarField as PrimeField>::Params::MODULUS)
+        .is_zero()
+}
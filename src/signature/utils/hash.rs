@@ -5,7 +5,7 @@ use blake2s_simd::Params;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 
-fn rng_from_message(personalization: &[u8], message: &[u8]) -> ChaChaRng {
+pub fn rng_from_message(personalization: &[u8], message: &[u8]) -> ChaChaRng {
     let hash = Params::new()
         .hash_length(32)
         .personal(personalization)
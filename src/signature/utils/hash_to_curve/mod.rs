@@ -0,0 +1,196 @@
+use crate::signature::utils::{errors::SignatureError, hash::hash_to_group};
+use ark_ec::{
+    models::SWModelParameters,
+    short_weierstrass_jacobian::{GroupAffine, GroupProjective},
+    AffineCurve, ProjectiveCurve,
+};
+use ark_ff::{Field, SquareRootField};
+use sha2::{Digest, Sha256};
+use std::fmt::Debug;
+
+pub mod bls12_381;
+
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+const BLAKE2S_PERSONALIZATION_BYTES: usize = 8;
+const LONG_DST_PERSONALIZATION: &[u8] = b"LONG_DST";
+
+/// A way of hashing messages to a group, selected by the signature schemes through a type
+/// parameter.
+pub trait HashToCurve<C: AffineCurve>: Debug + Clone + PartialEq {
+    /// The suite ID of the hash, used in the default domain separation tags of the BLS
+    /// ciphersuites.
+    fn suite_id() -> &'static [u8];
+
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<C::Projective, SignatureError>;
+}
+
+/// The original hash of this crate: a ChaCha rng seeded by Blake2s, personalized with the tag,
+/// samples points until one is valid. Works for any curve, but takes a variable number of
+/// iterations and matches no other implementation. Tags longer than the 8 bytes of a Blake2s
+/// personalization are prefixed to the message instead, with their length.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TryAndIncrement;
+
+impl<C: AffineCurve> HashToCurve<C> for TryAndIncrement {
+    fn suite_id() -> &'static [u8] {
+        b"BLAKE2S_CHACHA_TAI_"
+    }
+
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<C::Projective, SignatureError> {
+        if dst.len() <= BLAKE2S_PERSONALIZATION_BYTES {
+            return hash_to_group::<C>(dst, message);
+        }
+        let prefixed_message = [&(dst.len() as u64).to_le_bytes()[..], dst, message].concat();
+        hash_to_group::<C>(LONG_DST_PERSONALIZATION, &prefixed_message)
+    }
+}
+
+/// The random oracle hash-to-curve of RFC 9380: `expand_message_xmd` with SHA-256, simplified
+/// SWU on an isogenous curve, the isogeny map and cofactor clearing. Implemented for the groups
+/// of BLS12-381, with the suites `BLS12381G1_XMD:SHA-256_SSWU_RO_` and
+/// `BLS12381G2_XMD:SHA-256_SSWU_RO_`. Unlike `TryAndIncrement`, it doesn't loop, but it isn't
+/// constant-time either, see `map_to_curve_simple_swu`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sswu;
+
+/// The curve-specific steps of `Sswu`.
+pub trait SswuParameters: SWModelParameters + Sized {
+    /// The suite ID of RFC 9380, e.g. `BLS12381G1_XMD:SHA-256_SSWU_RO_`.
+    const SUITE_ID: &'static [u8];
+
+    /// The effective cofactor `h_eff`, as little-endian limbs.
+    const H_EFF: &'static [u64];
+
+    /// `hash_to_field` of RFC 9380 with `count = 2`.
+    fn hash_to_field(message: &[u8], dst: &[u8]) -> Result<[Self::BaseField; 2], SignatureError>;
+
+    /// `map_to_curve` of RFC 9380, including the isogeny map.
+    fn map_to_curve(u: Self::BaseField) -> GroupProjective<Self>;
+}
+
+impl<P: SswuParameters> HashToCurve<GroupAffine<P>> for Sswu {
+    fn suite_id() -> &'static [u8] {
+        P::SUITE_ID
+    }
+
+    fn hash_to_curve(dst: &[u8], message: &[u8]) -> Result<GroupProjective<P>, SignatureError> {
+        let [u_0, u_1] = P::hash_to_field(message, dst)?;
+        Ok((P::map_to_curve(u_0) + P::map_to_curve(u_1)).mul(P::H_EFF))
+    }
+}
+
+/// `expand_message_xmd` of RFC 9380 with SHA-256.
+pub fn expand_message_xmd(
+    message: &[u8],
+    dst: &[u8],
+    len_in_bytes: usize,
+) -> Result<Vec<u8>, SignatureError> {
+    const B_IN_BYTES: usize = 32;
+    const S_IN_BYTES: usize = 64;
+    let ell = (len_in_bytes + B_IN_BYTES - 1) / B_IN_BYTES;
+    if ell > 255 || len_in_bytes > 65535 {
+        return Err(SignatureError::ExpandMessageLength(len_in_bytes));
+    }
+    let dst = if dst.len() > 255 {
+        Sha256::new()
+            .chain(OVERSIZE_DST_PREFIX)
+            .chain(dst)
+            .finalize()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let dst_prime = [dst.as_slice(), &[dst.len() as u8]].concat();
+
+    let b_0 = Sha256::new()
+        .chain([0u8; S_IN_BYTES])
+        .chain(message)
+        .chain((len_in_bytes as u16).to_be_bytes())
+        .chain([0u8])
+        .chain(&dst_prime)
+        .finalize();
+    let mut b_i = Sha256::new()
+        .chain(b_0)
+        .chain([1u8])
+        .chain(&dst_prime)
+        .finalize();
+    let mut uniform_bytes = b_i.to_vec();
+    for i in 2..=ell {
+        let xored = b_0
+            .iter()
+            .zip(b_i.iter())
+            .map(|(a, b)| a ^ b)
+            .collect::<Vec<_>>();
+        b_i = Sha256::new()
+            .chain(xored)
+            .chain([i as u8])
+            .chain(&dst_prime)
+            .finalize();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    Ok(uniform_bytes)
+}
+
+/// The rational maps of an isogeny, as polynomial coefficients from the constant term up.
+pub struct IsogenyMap<F: Field> {
+    pub x_numerator: Vec<F>,
+    pub x_denominator: Vec<F>,
+    pub y_numerator: Vec<F>,
+    pub y_denominator: Vec<F>,
+}
+
+impl<F: Field> IsogenyMap<F> {
+    /// Maps a point of the isogenous curve. Returns `None` for the points mapped to the identity.
+    pub fn apply(&self, x: F, y: F) -> Option<(F, F)> {
+        let x_denominator = evaluate(&self.x_denominator, x).inverse()?;
+        let y_denominator = evaluate(&self.y_denominator, x).inverse()?;
+        Some((
+            evaluate(&self.x_numerator, x) * x_denominator,
+            y * evaluate(&self.y_numerator, x) * y_denominator,
+        ))
+    }
+}
+
+fn evaluate<F: Field>(coefficients: &[F], x: F) -> F {
+    coefficients
+        .iter()
+        .rev()
+        .fold(F::zero(), |acc, c| acc * x + c)
+}
+
+/// The simplified SWU map of RFC 9380 to `g(x) = x^3 + a * x + b`, for `a * b != 0` and `z` as in
+/// the RFC: a non-square with `g(x) - z` irreducible and `g(b / (z * a))` a square. `sgn0` is the
+/// sign function of the field.
+///
+/// This is variable-time: it branches on the exceptional case and on whether `g(x1)` is a square,
+/// and ark's inversions and square roots don't run in constant time. Its timing can leak the
+/// hashed message, so it shouldn't hash secrets.
+pub fn map_to_curve_simple_swu<F: SquareRootField>(
+    u: F,
+    a: F,
+    b: F,
+    z: F,
+    sgn0: fn(&F) -> bool,
+) -> (F, F) {
+    let g = |x: F| x.square() * x + a * x + b;
+    let z_u2 = z * u.square();
+    let tv1 = z_u2.square() + z_u2;
+    let x1 = match tv1.inverse() {
+        Some(tv1_inverse) => -b * a.inverse().unwrap() * (F::one() + tv1_inverse),
+        None => b * (z * a).inverse().unwrap(),
+    };
+    let gx1 = g(x1);
+    let (x, mut y) = match gx1.sqrt() {
+        Some(y1) => (x1, y1),
+        None => {
+            let x2 = z_u2 * x1;
+            // g(x2) = (z * u^2)^3 * g(x1), which is a square when g(x1) isn't.
+            (x2, g(x2).sqrt().unwrap())
+        }
+    };
+    if sgn0(&u) != sgn0(&y) {
+        y = -y;
+    }
+    (x, y)
+}
@@ -1,5 +1,8 @@
+pub mod batch;
 pub mod errors;
+pub mod group;
 pub mod hash;
+pub mod hash_to_curve;
 
 #[cfg(test)]
 pub mod tests;
@@ -0,0 +1,21 @@
+use crate::{dkg::errors::DKGError, signature::utils::errors::SignatureError};
+use ark_ec::PairingEngine;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VUFError<E: PairingEngine> {
+    #[error("DKG error: {0}")]
+    DKGError(#[from] DKGError<E>),
+    #[error("Signature error: {0}")]
+    SignatureError(#[from] SignatureError),
+    #[error("Could not generate evaluation domain")]
+    EvaluationDomainError,
+    #[error("Public key of participant {0} does not match the transcript")]
+    PublicKeyMismatch(usize),
+    #[error("Not enough valid partial evaluations: got {0}, need {1}")]
+    NotEnoughPartials(usize, usize),
+    #[error("Combined public key does not match the group public key")]
+    GroupKeyMismatch,
+    #[error("Evaluation output does not match the signature")]
+    OutputMismatch,
+}
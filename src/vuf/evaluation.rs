@@ -0,0 +1,21 @@
+use crate::signature::algebraic::{public_key::ProvenPublicKey, signature::Signature};
+use ark_ec::PairingEngine;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+
+/// A single participant's evaluation of the VUF on a message, computed from its DKG secret.
+#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PartialEvaluation<E: PairingEngine> {
+    pub participant_id: usize,
+    pub proven_public_key: ProvenPublicKey<E>,
+    pub signature: Signature<E>,
+}
+
+/// The VUF evaluation interpolated from `degree + 1` partial evaluations. `proven_public_key`
+/// is a key for the group public key, so anyone holding the transcript can verify `signature`
+/// and re-derive `output`.
+#[derive(Debug, Clone, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CombinedEvaluation<E: PairingEngine> {
+    pub proven_public_key: ProvenPublicKey<E>,
+    pub signature: Signature<E>,
+    pub output: E::Fqk,
+}
@@ -0,0 +1,3 @@
+pub mod errors;
+pub mod evaluation;
+pub mod threshold;
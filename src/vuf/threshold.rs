@@ -0,0 +1,453 @@
+use crate::{
+    dkg::{dealer::Dealer, domain::ParticipantDomain, output::DKGOutput, srs::SRS as DKGSRS},
+    signature::{
+        algebraic::{
+            keypair::{Keypair, PrivateKey},
+            public_key::{KeyProof, ProvenPublicKey, PublicKey},
+            signature::{Signature, SignatureProof},
+            srs::SRS,
+        },
+        scheme::BatchVerifiableSignatureScheme,
+        utils::errors::SignatureError,
+    },
+    vuf::{
+        errors::VUFError,
+        evaluation::{CombinedEvaluation, PartialEvaluation},
+    },
+};
+use ark_ec::{msm::VariableBaseMSM, PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand};
+use ark_std::collections::BTreeMap;
+use rand::Rng;
+use std::marker::PhantomData;
+
+/// Threshold evaluation of the algebraic VUF. After the DKG, participant `i` holds
+/// `h_g2^{f(omega^i)}` as `Dealer::accumulated_secret` and the DKG output holds the matching
+/// `a_i = g_g1^{f(omega^i)}`, which are exactly an algebraic signature keypair over an SRS with
+/// `g_1_g2 = h_g2` and `h_g1 = g_g1`.
+#[derive(Clone)]
+pub struct ThresholdVUF<E: PairingEngine> {
+    pub srs: SRS<E>,
+}
+
+impl<E: PairingEngine> ThresholdVUF<E> {
+    pub fn setup<R: Rng>(rng: &mut R, dkg_srs: &DKGSRS<E>) -> Result<Self, VUFError<E>> {
+        let srs = SRS {
+            g_1_g2: dkg_srs.h_g2,
+            h_g1: dkg_srs.g_g1,
+
+            g_2_g2: E::G2Projective::rand(rng).into_affine(),
+            g_3_g2: E::G2Projective::rand(rng).into_affine(),
+            g_4_g2: E::G2Projective::rand(rng).into_affine(),
+
+            hash_to_curve: PhantomData,
+        };
+        Ok(Self { srs })
+    }
+
+    pub fn keypair<
+        R: Rng,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    >(
+        &self,
+        rng: &mut R,
+        dealer: &Dealer<E, SSIG>,
+        output: &DKGOutput<E>,
+    ) -> Result<Keypair<E>, VUFError<E>> {
+        let participant_id = dealer.participant.id;
+        let pk = output.participant_public_key(participant_id)?;
+
+        Ok(Keypair {
+            srs: self.srs.clone(),
+            alpha: E::Fr::rand(rng),
+            beta: E::Fr::rand(rng),
+            private: PrivateKey {
+                sk: dealer.accumulated_secret,
+            },
+            public: PublicKey {
+                srs: self.srs.clone(),
+                pk,
+            },
+        })
+    }
+
+    pub fn partial_evaluate<
+        R: Rng,
+        SSIG: BatchVerifiableSignatureScheme<PublicKey = E::G2Affine, Secret = E::Fr>,
+    >(
+        &self,
+        rng: &mut R,
+        dealer: &Dealer<E, SSIG>,
+        output: &DKGOutput<E>,
+        message: &[u8],
+    ) -> Result<PartialEvaluation<E>, VUFError<E>> {
+        let keypair = self.keypair(rng, dealer, output)?;
+
+        Ok(PartialEvaluation {
+            participant_id: dealer.participant.id,
+            proven_public_key: keypair.prove_key()?,
+            signature: keypair.sign(message)?,
+        })
+    }
+
+    pub fn verify_partial(
+        &self,
+        output: &DKGOutput<E>,
+        partial: &PartialEvaluation<E>,
+        message: &[u8],
+    ) -> Result<(), VUFError<E>> {
+        let participant_id = partial.participant_id;
+        let pk = output.participant_public_key(participant_id)?;
+        if partial.proven_public_key.public_key.pk != pk {
+            return Err(VUFError::PublicKeyMismatch(participant_id));
+        }
+        if partial.proven_public_key.public_key.srs != self.srs {
+            return Err(SignatureError::SRSDifferent.into());
+        }
+
+        partial.proven_public_key.verify()?;
+        partial
+            .signature
+            .verify(partial.proven_public_key.clone(), message)?;
+
+        Ok(())
+    }
+
+    /// Verifies the given partial evaluations, skipping invalid and repeated ones, and
+    /// interpolates the first `degree + 1` valid ones at zero.
+    pub fn combine(
+        &self,
+        output: &DKGOutput<E>,
+        partials: &[PartialEvaluation<E>],
+        message: &[u8],
+    ) -> Result<CombinedEvaluation<E>, VUFError<E>> {
+        let threshold = output.threshold();
+        let mut valid_partials = BTreeMap::new();
+        for partial in partials {
+            if valid_partials.len() == threshold {
+                break;
+            }
+            if valid_partials.contains_key(&partial.participant_id) {
+                continue;
+            }
+            if self.verify_partial(output, partial, message).is_ok() {
+                valid_partials.insert(partial.participant_id, partial);
+            }
+        }
+        if valid_partials.len() < threshold {
+            return Err(VUFError::NotEnoughPartials(valid_partials.len(), threshold));
+        }
+
+        let domain = ParticipantDomain::<E::Fr>::new(output.num_participants)
+            .ok_or(VUFError::<E>::EvaluationDomainError)?;
+        let coefficients = domain
+            .lagrange_coefficients_at_zero(&valid_partials.keys().cloned().collect::<Vec<_>>())
+            .into_iter()
+            .map(|c| c.into_repr())
+            .collect::<Vec<_>>();
+
+        let interpolate_g1 = |elements: Vec<E::G1Affine>| {
+            VariableBaseMSM::multi_scalar_mul(&elements, &coefficients).into_affine()
+        };
+        let interpolate_g2 = |elements: Vec<E::G2Affine>| {
+            VariableBaseMSM::multi_scalar_mul(&elements, &coefficients).into_affine()
+        };
+        let partials = valid_partials.values().collect::<Vec<_>>();
+        let proven_public_key = ProvenPublicKey {
+            public_key: PublicKey {
+                srs: self.srs.clone(),
+                pk: interpolate_g1(
+                    partials
+                        .iter()
+                        .map(|p| p.proven_public_key.public_key.pk)
+                        .collect(),
+                ),
+            },
+            key_proof: KeyProof {
+                pi_1_g2: interpolate_g2(
+                    partials
+                        .iter()
+                        .map(|p| p.proven_public_key.key_proof.pi_1_g2)
+                        .collect(),
+                ),
+                pi_2_g2: interpolate_g2(
+                    partials
+                        .iter()
+                        .map(|p| p.proven_public_key.key_proof.pi_2_g2)
+                        .collect(),
+                ),
+                pi_1_g1: interpolate_g1(
+                    partials
+                        .iter()
+                        .map(|p| p.proven_public_key.key_proof.pi_1_g1)
+                        .collect(),
+                ),
+                pi_3_g1: interpolate_g1(
+                    partials
+                        .iter()
+                        .map(|p| p.proven_public_key.key_proof.pi_3_g1)
+                        .collect(),
+                ),
+            },
+        };
+        let signature = Signature {
+            signature_proof: SignatureProof {
+                pi_2_g1: interpolate_g1(
+                    partials
+                        .iter()
+                        .map(|p| p.signature.signature_proof.pi_2_g1)
+                        .collect(),
+                ),
+                pi_4_g1: interpolate_g1(
+                    partials
+                        .iter()
+                        .map(|p| p.signature.signature_proof.pi_4_g1)
+                        .collect(),
+                ),
+            },
+        };
+        if proven_public_key.public_key.pk != output.group_public_key {
+            return Err(VUFError::GroupKeyMismatch);
+        }
+        let output = signature.derive(proven_public_key.clone(), message)?;
+
+        Ok(CombinedEvaluation {
+            proven_public_key,
+            signature,
+            output,
+        })
+    }
+
+    /// Verifies a combined evaluation against the group public key and returns its output.
+    pub fn verify(
+        &self,
+        output: &DKGOutput<E>,
+        evaluation: &CombinedEvaluation<E>,
+        message: &[u8],
+    ) -> Result<E::Fqk, VUFError<E>> {
+        if evaluation.proven_public_key.public_key.pk != output.group_public_key {
+            return Err(VUFError::GroupKeyMismatch);
+        }
+        if evaluation.proven_public_key.public_key.srs != self.srs {
+            return Err(SignatureError::SRSDifferent.into());
+        }
+
+        evaluation.proven_public_key.verify()?;
+        let output = evaluation
+            .signature
+            .verify_and_derive(evaluation.proven_public_key.clone(), message)?;
+        if output != evaluation.output {
+            return Err(VUFError::OutputMismatch);
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        dkg::{
+            aggregator::DKGAggregator,
+            config::Config,
+            dealer::Dealer,
+            node::Node,
+            output::DKGOutput,
+            participant::{committee_digest, Participant, ParticipantState},
+            srs::SRS,
+        },
+        signature::{
+            bls::{srs::SRS as BLSSRS, BLSSignature, BLSSignatureG1, BLSSignatureG2},
+            scheme::SignatureScheme,
+        },
+        vuf::{errors::VUFError, threshold::ThresholdVUF},
+    };
+    use ark_bls12_381::{Bls12_381, G2Projective};
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::{UniformRand, Zero};
+    use rand::thread_rng;
+
+    type TestDealer = Dealer<Bls12_381, BLSSignature<BLSSignatureG1<Bls12_381>>>;
+
+    fn run_dkg(
+        num_nodes: usize,
+        degree: usize,
+    ) -> (SRS<Bls12_381>, Vec<TestDealer>, DKGOutput<Bls12_381>) {
+        let rng = &mut thread_rng();
+        let srs = SRS::<Bls12_381>::setup(rng).unwrap();
+        let bls_sig = BLSSignature::<BLSSignatureG1<Bls12_381>> {
+            srs: BLSSRS {
+                g_public_key: srs.h_g2,
+                g_signature: srs.g_g1,
+            },
+        };
+        let bls_pok = BLSSignature::<BLSSignatureG2<Bls12_381>> {
+            srs: BLSSRS {
+                g_public_key: srs.g_g1,
+                g_signature: srs.h_g2,
+            },
+        };
+
+        let u_1 = G2Projective::rand(rng).into_affine();
+
+        let mut dealers = vec![];
+        for i in 0..num_nodes {
+            let dealer_keypair_sig = bls_sig.generate_keypair(rng).unwrap();
+            let participant = Participant {
+                state: ParticipantState::Dealer,
+                ..Participant::register(rng, &bls_sig, i, &dealer_keypair_sig.0).unwrap()
+            };
+            dealers.push(Dealer {
+                private_key_sig: dealer_keypair_sig.0,
+                accumulated_secret: G2Projective::zero().into_affine(),
+                participant,
+            });
+        }
+        let participants = dealers
+            .iter()
+            .map(|d| d.participant.clone())
+            .collect::<Vec<_>>();
+
+        let dkg_config = Config {
+            srs: srs.clone(),
+            u_1,
+            degree,
+            session_id: b"session".to_vec(),
+            committee_digest: committee_digest(&participants).unwrap(),
+        };
+
+        let mut aggregator = DKGAggregator::new(
+            rng,
+            dkg_config.clone(),
+            bls_pok.clone(),
+            bls_sig.clone(),
+            participants.clone().into_iter().enumerate().collect(),
+        )
+        .unwrap();
+        for dealer in dealers.iter() {
+            let mut node = Node {
+                aggregator: DKGAggregator::new(
+                    rng,
+                    dkg_config.clone(),
+                    bls_pok.clone(),
+                    bls_sig.clone(),
+                    participants.clone().into_iter().enumerate().collect(),
+                )
+                .unwrap(),
+                dealer: dealer.clone(),
+            };
+            let share = node.share(rng).unwrap();
+            aggregator.receive_share(rng, &share).unwrap();
+        }
+
+        let dealers = dealers
+            .into_iter()
+            .map(|dealer| {
+                let mut node = Node {
+                    aggregator: DKGAggregator::new(
+                        rng,
+                        dkg_config.clone(),
+                        bls_pok.clone(),
+                        bls_sig.clone(),
+                        participants.clone().into_iter().enumerate().collect(),
+                    )
+                    .unwrap(),
+                    dealer,
+                };
+                node.receive_transcript_and_decrypt(rng, aggregator.transcript.clone())
+                    .unwrap();
+                node.dealer
+            })
+            .collect();
+
+        let output = aggregator.output(rng, &aggregator.transcript).unwrap();
+
+        (srs, dealers, output)
+    }
+
+    #[test]
+    fn test_threshold_vuf() {
+        let rng = &mut thread_rng();
+        let (srs, dealers, ref output) = run_dkg(4, 2);
+        let vuf = ThresholdVUF::setup(rng, &srs).unwrap();
+        let message = b"hello";
+
+        let partials = dealers
+            .iter()
+            .map(|dealer| {
+                let partial = vuf
+                    .partial_evaluate(rng, dealer, output, &message[..])
+                    .unwrap();
+                vuf.verify_partial(output, &partial, &message[..]).unwrap();
+                partial
+            })
+            .collect::<Vec<_>>();
+
+        let evaluation = vuf.combine(output, &partials[..3], &message[..]).unwrap();
+        let other_evaluation = vuf.combine(output, &partials[1..], &message[..]).unwrap();
+        assert_eq!(evaluation.output, other_evaluation.output);
+        assert_eq!(
+            vuf.verify(output, &evaluation, &message[..]).unwrap(),
+            evaluation.output
+        );
+
+        let other_message_evaluation = vuf
+            .combine(
+                output,
+                &dealers[1..]
+                    .iter()
+                    .map(|dealer| {
+                        vuf.partial_evaluate(rng, dealer, output, &b"goodbye"[..])
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>(),
+                &b"goodbye"[..],
+            )
+            .unwrap();
+        assert_ne!(evaluation.output, other_message_evaluation.output);
+        vuf.verify(output, &other_message_evaluation, &message[..])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_threshold_vuf_invalid_partials() {
+        let rng = &mut thread_rng();
+        let (srs, dealers, ref output) = run_dkg(4, 2);
+        let vuf = ThresholdVUF::setup(rng, &srs).unwrap();
+        let message = b"hello";
+
+        let mut partials = dealers
+            .iter()
+            .map(|dealer| {
+                vuf.partial_evaluate(rng, dealer, output, &message[..])
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        partials[0] = vuf
+            .partial_evaluate(rng, &dealers[0], output, &b"goodbye"[..])
+            .unwrap();
+        partials[1].participant_id = 2;
+        vuf.verify_partial(output, &partials[0], &message[..])
+            .unwrap_err();
+        vuf.verify_partial(output, &partials[1], &message[..])
+            .unwrap_err();
+
+        match vuf.combine(output, &partials, &message[..]) {
+            Err(VUFError::NotEnoughPartials(2, 3)) => {}
+            _ => panic!("expected not enough partials"),
+        }
+        let evaluation = vuf
+            .combine(
+                output,
+                &[
+                    partials[2].clone(),
+                    partials[3].clone(),
+                    vuf.partial_evaluate(rng, &dealers[1], output, &message[..])
+                        .unwrap(),
+                ],
+                &message[..],
+            )
+            .unwrap();
+        vuf.verify(output, &evaluation, &message[..]).unwrap();
+    }
+}